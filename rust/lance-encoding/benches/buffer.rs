@@ -1,7 +1,7 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-FileCopyrightText: Copyright The Lance Authors
 
-use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion, Throughput};
 use lance_encoding::buffer::LanceBuffer;
 
 const NUM_VALUES: &[usize] = &[1024 * 1024, 32 * 1024, 8 * 1024];
@@ -59,18 +59,66 @@ fn bench_zip(c: &mut Criterion) {
     }
 }
 
+fn bench_concat(c: &mut Criterion) {
+    const NUM_BUFFERS: usize = 10 * 1024;
+    const BUFFER_SIZE: usize = 64;
+    let mut group = c.benchmark_group("concat_10Ki_64B");
+
+    group.throughput(Throughput::Bytes((NUM_BUFFERS * BUFFER_SIZE) as u64));
+
+    let mut buffers = (0..NUM_BUFFERS)
+        .map(|_| {
+            let data: Vec<u8> = (0..BUFFER_SIZE).map(|_| rand::random::<u8>()).collect();
+            LanceBuffer::Owned(data)
+        })
+        .collect::<Vec<_>>();
+    for buffer in buffers.iter_mut() {
+        // Put the buffers in borrowed mode so the benchmark iterations can clone them cheaply
+        buffer.borrow_and_clone();
+    }
+    let buffers = &buffers;
+
+    group.bench_function("concat", |b| {
+        b.iter(|| black_box(LanceBuffer::concat(buffers)))
+    });
+
+    // The input buffers are cloned outside of the measurement since concat_into_one consumes them
+    let clone_buffers = || {
+        buffers
+            .iter()
+            .map(|buf| buf.try_clone().unwrap())
+            .collect::<Vec<_>>()
+    };
+
+    group.bench_function("concat_into_one", |b| {
+        b.iter_batched(
+            clone_buffers,
+            |buffers| black_box(LanceBuffer::concat_into_one(buffers)),
+            BatchSize::LargeInput,
+        )
+    });
+
+    group.bench_function("concat_into_one_aligned_64", |b| {
+        b.iter_batched(
+            clone_buffers,
+            |buffers| black_box(LanceBuffer::concat_into_one_aligned(buffers, 64)),
+            BatchSize::LargeInput,
+        )
+    });
+}
+
 #[cfg(target_os = "linux")]
 criterion_group!(
     name=benches;
     config = Criterion::default().significance_level(0.1).sample_size(10)
         .with_profiler(pprof::criterion::PProfProfiler::new(100, pprof::criterion::Output::Flamegraph(None)));
-    targets = bench_zip);
+    targets = bench_zip, bench_concat);
 
 // Non-linux version does not support pprof.
 #[cfg(not(target_os = "linux"))]
 criterion_group!(
     name=benches;
     config = Criterion::default().significance_level(0.1).sample_size(10);
-    targets = bench_zip);
+    targets = bench_zip, bench_concat);
 
 criterion_main!(benches);
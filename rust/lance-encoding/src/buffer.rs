@@ -3,14 +3,68 @@
 
 //! Utilities for byte arrays
 
-use std::{ops::Deref, panic::RefUnwindSafe, ptr::NonNull, sync::Arc};
+use std::{
+    alloc::Layout,
+    borrow::Cow,
+    collections::HashMap,
+    ops::{Deref, Range},
+    panic::RefUnwindSafe,
+    ptr::NonNull,
+    sync::Arc,
+};
 
-use arrow_buffer::{ArrowNativeType, Buffer, MutableBuffer, ScalarBuffer};
-use itertools::Either;
+use arrow_array::FixedSizeBinaryArray;
+use arrow_buffer::{
+    bit_chunk_iterator::{BitChunks, UnalignedBitChunk},
+    bit_iterator::BitIndexIterator,
+    bit_util, ArrowNativeType, BooleanBufferBuilder, Buffer, MutableBuffer, ScalarBuffer,
+    ToByteSlice,
+};
+use hyperloglogplus::{HyperLogLog, HyperLogLogPlus};
+use itertools::{Either, Itertools};
+use num_traits::{CheckedAdd, PrimInt, Signed, WrappingNeg};
+use rand::{Rng, SeedableRng};
 use snafu::location;
 
 use lance_core::{utils::bit::is_pwr_two, Error, Result};
 
+// The number of bytes sampled from each region of a buffer when estimating entropy
+const ENTROPY_SAMPLE_BYTES: usize = 4096;
+
+// The number of evenly spaced windows sampled when estimating the compressed size
+#[cfg(feature = "zstd")]
+const COMPRESSION_SAMPLE_WINDOWS: usize = 8;
+
+// The maximum number of bytes needed to encode a u64 as a LEB128 varint
+const MAX_VARINT_BYTES: usize = 10;
+
+// A borrowed buffer using less than 1/COMPACT_RATIO of its allocation is copied by compact
+const COMPACT_RATIO: usize = 4;
+
+// An upper bound on the compression ratio of an LZ4 block, each byte of a match length can
+// extend the match by at most 255 bytes
+#[cfg(feature = "lz4")]
+const LZ4_MAX_COMPRESSION_RATIO: usize = 255;
+
+// The magic bytes at the start of a block written by pack_block
+#[cfg(feature = "checksum")]
+const PACKED_BLOCK_MAGIC: &[u8; 4] = b"LNCB";
+
+// The size of the pack_block header: magic, codec id, uncompressed length, and crc32c
+#[cfg(feature = "checksum")]
+const PACKED_BLOCK_HEADER_BYTES: usize = 4 + 1 + 8 + 4;
+
+// Maps each byte to the same byte with its bits in reverse order
+const BIT_REVERSE_TABLE: [u8; 256] = {
+    let mut table = [0_u8; 256];
+    let mut i = 0;
+    while i < 256 {
+        table[i] = (i as u8).reverse_bits();
+        i += 1;
+    }
+    table
+};
+
 /// A copy-on-write byte buffer
 ///
 /// It can be created from read-only buffers (e.g. bytes::Bytes or arrow_buffer::Buffer), e.g. "borrowed"
@@ -23,9 +77,27 @@ use lance_core::{utils::bit::is_pwr_two, Error, Result};
 ///
 /// If you need to clone a LanceBuffer you can use borrow_and_clone() which will make sure that the buffer
 /// is in borrowed mode before cloning.  This is a zero copy operation (but requires &mut self).
+///
+/// A buffer can also be put into "shared" mode with share().  Shared buffers are reference counted and
+/// can be cloned (with try_clone()) without &mut self.  Moving an owned buffer into shared mode copies
+/// the data once since a `Vec<u8>` cannot become an `Arc<[u8]>` in place.  After that, converting a shared
+/// buffer into an Arrow buffer, bytes::Bytes, or a borrowed buffer is zero-copy, only converting it
+/// back into owned mode (e.g. into_owned() or mutating it) copies.
+///
+/// Buffers created by mmap() are in "mapped" mode.  They behave like borrowed buffers but also keep
+/// the mapping so that advise() can reach it.
+///
+/// Methods that modify the buffer in place (e.g. push_value()) first switch it into owned mode, which
+/// may copy the data if it is not already owned.
+///
+/// Methods that work with bitmaps use the Arrow convention of *bitwise* little-endian, bit `i` is bit
+/// `i % 8` of byte `i / 8`.
 pub enum LanceBuffer {
     Borrowed(Buffer),
     Owned(Vec<u8>),
+    Shared(Arc<[u8]>),
+    #[cfg(feature = "mmap")]
+    Mapped(MappedBuffer),
 }
 
 // Compares equality of the buffers, ignoring owned / unowned status
@@ -36,12 +108,106 @@ impl PartialEq for LanceBuffer {
             (Self::Owned(l0), Self::Owned(r0)) => l0 == r0,
             (Self::Borrowed(l0), Self::Owned(r0)) => l0.as_slice() == r0.as_slice(),
             (Self::Owned(l0), Self::Borrowed(r0)) => l0.as_slice() == r0.as_slice(),
+            (l0, r0) => l0.as_ref() == r0.as_ref(),
         }
     }
 }
 
 impl Eq for LanceBuffer {}
 
+// Hashes the bytes of the buffer, ignoring owned / unowned status, to be consistent with PartialEq
+impl std::hash::Hash for LanceBuffer {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.as_ref().hash(state);
+    }
+}
+
+// Orders buffers by lexicographic comparison of their bytes, ignoring owned / unowned status
+impl PartialOrd for LanceBuffer {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for LanceBuffer {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.as_ref().cmp(other.as_ref())
+    }
+}
+
+// Serializes the bytes of the buffer, as a base64 string for human-readable formats (e.g. JSON)
+// and as raw bytes otherwise (e.g. bincode)
+#[cfg(feature = "serde")]
+impl serde::Serialize for LanceBuffer {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&self.as_base64())
+        } else {
+            serializer.serialize_bytes(self)
+        }
+    }
+}
+
+// Deserializes into an owned buffer (and so makes no assumptions about alignment)
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for LanceBuffer {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        struct LanceBufferVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for LanceBufferVisitor {
+            type Value = LanceBuffer;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a byte array or a base64 encoded string")
+            }
+
+            fn visit_str<E: serde::de::Error>(
+                self,
+                value: &str,
+            ) -> std::result::Result<Self::Value, E> {
+                LanceBuffer::from_base64(value).map_err(E::custom)
+            }
+
+            fn visit_bytes<E: serde::de::Error>(
+                self,
+                value: &[u8],
+            ) -> std::result::Result<Self::Value, E> {
+                Ok(LanceBuffer::Owned(value.to_vec()))
+            }
+
+            fn visit_byte_buf<E: serde::de::Error>(
+                self,
+                value: Vec<u8>,
+            ) -> std::result::Result<Self::Value, E> {
+                Ok(LanceBuffer::Owned(value))
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> std::result::Result<Self::Value, A::Error> {
+                let mut data = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+                while let Some(byte) = seq.next_element::<u8>()? {
+                    data.push(byte);
+                }
+                Ok(LanceBuffer::Owned(data))
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            // Accept either a base64 string or an array of bytes
+            deserializer.deserialize_any(LanceBufferVisitor)
+        } else {
+            deserializer.deserialize_byte_buf(LanceBufferVisitor)
+        }
+    }
+}
+
 impl std::fmt::Debug for LanceBuffer {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let preview = if self.len() > 10 {
@@ -64,16 +230,32 @@ impl std::fmt::Debug for LanceBuffer {
                     buffer.len()
                 )
             }
+            Self::Shared(buffer) => write!(
+                f,
+                "LanceBuffer::Shared(bytes={} #bytes={})",
+                preview,
+                buffer.len()
+            ),
+            #[cfg(feature = "mmap")]
+            Self::Mapped(mapped) => write!(
+                f,
+                "LanceBuffer::Mapped(bytes={} #bytes={})",
+                preview,
+                mapped.buffer.len()
+            ),
         }
     }
 }
 
 impl LanceBuffer {
-    /// Convert into a mutable buffer.  If this is a borrowed buffer, the data will be copied.
+    /// Convert into a mutable buffer.  If this is not an owned buffer, the data will be copied.
     pub fn into_owned(self) -> Vec<u8> {
         match self {
             Self::Borrowed(buffer) => buffer.to_vec(),
             Self::Owned(buffer) => buffer,
+            Self::Shared(buffer) => buffer.to_vec(),
+            #[cfg(feature = "mmap")]
+            Self::Mapped(mapped) => mapped.buffer.to_vec(),
         }
     }
 
@@ -82,9 +264,21 @@ impl LanceBuffer {
         match self {
             Self::Borrowed(buffer) => buffer,
             Self::Owned(buffer) => Buffer::from_vec(buffer),
+            Self::Shared(buffer) => Self::shared_to_arrow(buffer),
+            #[cfg(feature = "mmap")]
+            Self::Mapped(mapped) => mapped.buffer,
         }
     }
 
+    // Wraps shared data in an Arrow buffer without copying
+    fn shared_to_arrow(data: Arc<[u8]>) -> Buffer {
+        let ptr = NonNull::new(data.as_ptr() as *mut u8).expect("should be a valid pointer");
+        let len = data.len();
+        // SAFETY: the pointer is valid for len bytes for as long as the Arc is alive and the Arc
+        // is kept alive by the buffer.  Data behind an Arc<[u8]> is never mutated.
+        unsafe { Buffer::from_custom_allocation(ptr, len, Arc::new(data)) }
+    }
+
     /// Returns an owned buffer of the given size with all bits set to 0
     pub fn all_unset(len: usize) -> Self {
         Self::Owned(vec![0; len])
@@ -105,11 +299,35 @@ impl LanceBuffer {
         hex::encode_upper(self)
     }
 
+    /// Converts the buffer into a base64 string (using the standard alphabet with padding)
+    #[cfg(feature = "base64")]
+    pub fn as_base64(&self) -> String {
+        use base64::Engine;
+        base64::engine::general_purpose::STANDARD.encode(self)
+    }
+
+    /// Decodes a base64 string (using the standard alphabet with padding) into an owned buffer
+    ///
+    /// Returns an error if the string is not valid base64
+    #[cfg(feature = "base64")]
+    pub fn from_base64(s: &str) -> Result<Self> {
+        use base64::Engine;
+        base64::engine::general_purpose::STANDARD
+            .decode(s)
+            .map(Self::Owned)
+            .map_err(|err| {
+                Error::invalid_input(format!("invalid base64 string: {}", err), location!())
+            })
+    }
+
     /// Combine multiple buffers into a single buffer
     ///
     /// This does involve a data copy (and allocation of a new buffer)
     pub fn concat(buffers: &[Self]) -> Self {
         let total_len = buffers.iter().map(|b| b.len()).sum();
+        if total_len == 0 {
+            return Self::empty();
+        }
         let mut data = Vec::with_capacity(total_len);
         for buffer in buffers {
             data.extend_from_slice(buffer.as_ref());
@@ -164,11 +382,40 @@ impl LanceBuffer {
         }
     }
 
+    /// Create a LanceBuffer from a bytes::Bytes object, copying small buffers
+    ///
+    /// If `bytes` is shorter than `copy_below` then the data is copied into an owned buffer
+    /// and `bytes` is dropped.  This is useful when `bytes` is a small slice of a much larger
+    /// allocation, which would otherwise be kept alive by the returned buffer.
+    ///
+    /// Otherwise this behaves the same as [`Self::from_bytes`].
+    pub fn from_bytes_threshold(
+        bytes: bytes::Bytes,
+        bytes_per_value: u64,
+        copy_below: usize,
+    ) -> Self {
+        if bytes.len() < copy_below {
+            Self::Owned(bytes.to_vec())
+        } else {
+            Self::from_bytes(bytes, bytes_per_value)
+        }
+    }
+
     /// Convert a buffer into a bytes::Bytes object
+    ///
+    /// This avoids a copy if the buffer is owned, is in shared mode, or is the sole reference to
+    /// an allocation made by a Vec.  Otherwise (e.g. the buffer is borrowed from another buffer
+    /// or came from [`Self::from_bytes`]) the data is copied.
     pub fn into_bytes(self) -> bytes::Bytes {
         match self {
             Self::Owned(buf) => buf.into(),
-            Self::Borrowed(buf) => buf.into_vec::<u8>().unwrap().into(),
+            Self::Borrowed(buf) => buf
+                .into_vec::<u8>()
+                .map(bytes::Bytes::from)
+                .unwrap_or_else(|buf| bytes::Bytes::copy_from_slice(&buf)),
+            Self::Shared(buf) => bytes::Bytes::from_owner(buf),
+            #[cfg(feature = "mmap")]
+            Self::Mapped(mapped) => bytes::Bytes::copy_from_slice(&mapped.buffer),
         }
     }
 
@@ -179,6 +426,9 @@ impl LanceBuffer {
         match self {
             Self::Borrowed(_) => self,
             Self::Owned(buffer) => Self::Borrowed(Buffer::from_vec(buffer)),
+            Self::Shared(buffer) => Self::Borrowed(Self::shared_to_arrow(buffer)),
+            #[cfg(feature = "mmap")]
+            Self::Mapped(mapped) => Self::Borrowed(mapped.buffer),
         }
     }
 
@@ -187,6 +437,9 @@ impl LanceBuffer {
         match self {
             Self::Borrowed(buffer) => Self::Owned(buffer.to_vec()),
             Self::Owned(buffer) => Self::Owned(buffer.clone()),
+            Self::Shared(buffer) => Self::Owned(buffer.to_vec()),
+            #[cfg(feature = "mmap")]
+            Self::Mapped(mapped) => Self::Owned(mapped.buffer.to_vec()),
         }
     }
 
@@ -202,13 +455,45 @@ impl LanceBuffer {
                 *self = Self::Borrowed(buffer.clone());
                 Self::Borrowed(buffer)
             }
+            Self::Shared(buffer) => {
+                let buffer = Self::shared_to_arrow(buffer.clone());
+                *self = Self::Borrowed(buffer.clone());
+                Self::Borrowed(buffer)
+            }
+            #[cfg(feature = "mmap")]
+            Self::Mapped(mapped) => Self::Mapped(mapped.clone()),
+        }
+    }
+
+    /// Creates a clone of the buffer that shares the same memory
+    ///
+    /// If the buffer is owned then it is first moved into shared mode.  This copies the data
+    /// once (a `Vec<u8>` cannot be converted into an `Arc<[u8]>` in place).  After that, or if
+    /// the buffer is already borrowed or shared, this only increments a reference count.  Once
+    /// shared, further clones can be made with [`Self::try_clone`] which only needs `&self`.
+    pub fn share(&mut self) -> Self {
+        match self {
+            Self::Borrowed(buffer) => Self::Borrowed(buffer.clone()),
+            Self::Owned(buffer) => {
+                let shared = Arc::<[u8]>::from(std::mem::take(buffer));
+                *self = Self::Shared(shared.clone());
+                Self::Shared(shared)
+            }
+            Self::Shared(buffer) => Self::Shared(buffer.clone()),
+            #[cfg(feature = "mmap")]
+            Self::Mapped(mapped) => Self::Mapped(mapped.clone()),
         }
     }
 
     /// Clones the buffer but fails if the buffer is in owned mode
+    ///
+    /// Borrowed and shared buffers are cloned without copying the data
     pub fn try_clone(&self) -> Result<Self> {
         match self {
             Self::Borrowed(buffer) => Ok(Self::Borrowed(buffer.clone())),
+            Self::Shared(buffer) => Ok(Self::Shared(buffer.clone())),
+            #[cfg(feature = "mmap")]
+            Self::Mapped(mapped) => Ok(Self::Mapped(mapped.clone())),
             Self::Owned(_) => Err(Error::Internal {
                 message: "try_clone called on an owned buffer".to_string(),
                 location: location!(),
@@ -221,6 +506,9 @@ impl LanceBuffer {
         match self {
             Self::Borrowed(buffer) => Self::Owned(buffer.to_vec()),
             Self::Owned(buffer) => Self::Owned(buffer.clone()),
+            Self::Shared(buffer) => Self::Owned(buffer.to_vec()),
+            #[cfg(feature = "mmap")]
+            Self::Mapped(mapped) => Self::Owned(mapped.buffer.to_vec()),
         }
     }
 
@@ -280,6 +568,18 @@ impl LanceBuffer {
         }
     }
 
+    /// Reinterprets a LanceBuffer into a Vec<T>, failing if the buffer is not made up of whole
+    /// values of `T`
+    ///
+    /// This is the same as [`Self::borrow_to_typed_slice`] (including the zero-copy fast path
+    /// when the buffer is aligned) but returns an error instead of panicking if the buffer
+    /// length is not a multiple of the size of `T`.  It should be used when the buffer comes
+    /// from untrusted data such as a file.
+    pub fn try_borrow_to_typed_slice<T: ArrowNativeType>(&mut self) -> Result<ScalarBuffer<T>> {
+        self.check_value_width(std::mem::size_of::<T>())?;
+        Ok(self.borrow_to_typed_slice())
+    }
+
     /// Concatenates multiple buffers into a single buffer, consuming the input buffers
     ///
     /// If there is only one buffer, it will be returned as is
@@ -292,6 +592,9 @@ impl LanceBuffer {
         for buffer in &buffers {
             total_len += buffer.len();
         }
+        if total_len == 0 {
+            return Self::empty();
+        }
 
         let mut data = Vec::with_capacity(total_len);
         for buffer in buffers {
@@ -301,17 +604,37 @@ impl LanceBuffer {
         Self::Owned(data)
     }
 
+    /// Concatenates multiple buffers into a single buffer whose start is aligned to `align`
+    /// bytes, consuming the input buffers
+    ///
+    /// This always copies (even if there is only one buffer) and returns a borrowed buffer.
+    /// The result can then be reinterpreted (e.g. with [`Self::borrow_to_typed_slice`]) as any
+    /// type with an alignment of at most `align` without copying.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `align` is not a power of two
+    pub fn concat_into_one_aligned(buffers: Vec<Self>, align: usize) -> Self {
+        Self::Borrowed(AlignedAllocation::concat(&buffers, align).into_buffer())
+    }
+
     /// Zips multiple buffers into a single buffer, consuming the input buffers
     ///
     /// Unlike concat_into_one this "zips" the buffers, interleaving the values
+    ///
+    /// If any width is not a whole number of bytes then the inputs and the output are bit-packed,
+    /// e.g. zipping a 3-bit buffer with a 5-bit buffer produces one byte per value.
     pub fn zip_into_one(buffers: Vec<(Self, u64)>, num_values: u64) -> Result<Self> {
-        let bytes_per_value = buffers.iter().map(|(_, bits_per_value)| {
-            if bits_per_value % 8 == 0 {
-                Ok(bits_per_value / 8)
-            } else {
-                Err(Error::InvalidInput { source: format!("LanceBuffer::zip_into_one only supports full-byte buffers currently and received a buffer with {} bits per value", bits_per_value).into(), location: location!() })
-            }
-        }).collect::<Result<Vec<_>>>()?;
+        if buffers
+            .iter()
+            .any(|(_, bits_per_value)| bits_per_value % 8 != 0)
+        {
+            return Self::zip_bits_into_one(&buffers, num_values);
+        }
+        let bytes_per_value = buffers
+            .iter()
+            .map(|(_, bits_per_value)| bits_per_value / 8)
+            .collect::<Vec<_>>();
         let total_bytes_per_value = bytes_per_value.iter().sum::<u64>();
         let total_bytes = (total_bytes_per_value * num_values) as usize;
 
@@ -337,6 +660,110 @@ impl LanceBuffer {
         Ok(Self::Owned(zipped))
     }
 
+    // The slow path of zip_into_one, used when any buffer has a partial-byte width
+    fn zip_bits_into_one(buffers: &[(Self, u64)], num_values: u64) -> Result<Self> {
+        let num_values = num_values as usize;
+        for (buffer, bits_per_value) in buffers {
+            let required_bytes = (*bits_per_value as usize * num_values).div_ceil(8);
+            if buffer.len() < required_bytes {
+                return Err(Error::invalid_input(
+                    format!(
+                        "a buffer of {} bytes is too short to hold {} values of {} bits",
+                        buffer.len(),
+                        num_values,
+                        bits_per_value
+                    ),
+                    location!(),
+                ));
+            }
+        }
+        let total_bits_per_value = buffers
+            .iter()
+            .map(|(_, bits_per_value)| *bits_per_value as usize)
+            .sum::<usize>();
+        let mut zipped = vec![0_u8; (total_bits_per_value * num_values).div_ceil(8)];
+        let mut field_offset = 0;
+        for (buffer, bits_per_value) in buffers {
+            let bits_per_value = *bits_per_value as usize;
+            for value_idx in 0..num_values {
+                copy_bits(
+                    buffer,
+                    value_idx * bits_per_value,
+                    &mut zipped,
+                    value_idx * total_bits_per_value + field_offset,
+                    bits_per_value,
+                );
+            }
+            field_offset += bits_per_value;
+        }
+        Ok(Self::Owned(zipped))
+    }
+
+    /// Splits a buffer created by [`Self::zip_into_one`] back into one owned buffer per column
+    ///
+    /// `widths_in_bits` gives the width of each column's values, in the same order they were
+    /// zipped, and `num_values` is the number of values in each column.
+    ///
+    /// If any width is not a whole number of bytes then the values are assumed to be packed
+    /// at the bit level and each output buffer is bit-packed in the same way (see
+    /// [`Self::zip_into_one`]).
+    ///
+    /// Returns an error if the buffer length is not the combined width of the columns times
+    /// `num_values` (rounded up to a whole byte).
+    pub fn unzip_into_many(
+        buffer: Self,
+        widths_in_bits: &[u64],
+        num_values: u64,
+    ) -> Result<Vec<Self>> {
+        if widths_in_bits
+            .iter()
+            .all(|bits_per_value| bits_per_value % 8 == 0)
+        {
+            let widths_in_bytes = widths_in_bits
+                .iter()
+                .map(|bits_per_value| (bits_per_value / 8) as usize)
+                .collect::<Vec<_>>();
+            return buffer.split_soa(&widths_in_bytes, num_values);
+        }
+
+        let num_values = num_values as usize;
+        let total_bits_per_value = widths_in_bits
+            .iter()
+            .map(|bits_per_value| *bits_per_value as usize)
+            .sum::<usize>();
+        let expected_len = (total_bits_per_value * num_values).div_ceil(8);
+        if buffer.len() != expected_len {
+            return Err(Error::invalid_input(
+                format!(
+                    "a buffer of {} bytes cannot hold {} values of {} bits each",
+                    buffer.len(),
+                    num_values,
+                    total_bits_per_value
+                ),
+                location!(),
+            ));
+        }
+        let mut field_offset = 0;
+        Ok(widths_in_bits
+            .iter()
+            .map(|bits_per_value| {
+                let bits_per_value = *bits_per_value as usize;
+                let mut unzipped = vec![0_u8; (bits_per_value * num_values).div_ceil(8)];
+                for value_idx in 0..num_values {
+                    copy_bits(
+                        &buffer,
+                        value_idx * total_bits_per_value + field_offset,
+                        &mut unzipped,
+                        value_idx * bits_per_value,
+                        bits_per_value,
+                    );
+                }
+                field_offset += bits_per_value;
+                Self::Owned(unzipped)
+            })
+            .collect())
+    }
+
     /// Create a LanceBuffer from a slice
     ///
     /// This is NOT a zero-copy operation.  We can't even create a borrowed buffer because
@@ -353,11 +780,23 @@ impl LanceBuffer {
         Self::Owned(Vec::from(array))
     }
 
+    /// Create a LanceBuffer from a slice of values
+    ///
+    /// The values are written in native-endian order.  Like [`Self::copy_slice`] this is NOT
+    /// a zero-copy operation.  If you have an owned `Vec<T>` then [`Self::reinterpret_vec`]
+    /// can be used instead to avoid the copy.
+    pub fn from_typed_slice<T: ArrowNativeType>(slice: &[T]) -> Self {
+        Self::Owned(slice.to_byte_slice().to_vec())
+    }
+
     #[allow(clippy::len_without_is_empty)]
     pub fn len(&self) -> usize {
         match self {
             Self::Borrowed(buffer) => buffer.len(),
             Self::Owned(buffer) => buffer.len(),
+            Self::Shared(buffer) => buffer.len(),
+            #[cfg(feature = "mmap")]
+            Self::Mapped(mapped) => mapped.buffer.len(),
         }
     }
 
@@ -366,7 +805,8 @@ impl LanceBuffer {
     /// Doing so allows the same memory region to be shared between lance buffers.
     /// # Panics
     /// Panics if `(offset + length)` is larger than the existing length.
-    /// If the buffer is owned this method will require a copy.
+    /// If the buffer is owned this method will require a copy.  Slices of a shared buffer are
+    /// borrowed buffers that keep the shared data alive.
     pub fn slice_with_length(&self, offset: usize, length: usize) -> Self {
         let original_buffer_len = self.len();
         assert!(
@@ -376,9 +816,77 @@ impl LanceBuffer {
         match self {
             Self::Borrowed(buffer) => Self::Borrowed(buffer.slice_with_length(offset, length)),
             Self::Owned(buffer) => Self::Owned(buffer[offset..offset + length].to_vec()),
+            Self::Shared(buffer) => Self::Borrowed(
+                Self::shared_to_arrow(buffer.clone()).slice_with_length(offset, length),
+            ),
+            #[cfg(feature = "mmap")]
+            Self::Mapped(mapped) => Self::Mapped(MappedBuffer {
+                buffer: mapped.buffer.slice_with_length(offset, length),
+                mmap: mapped.mmap.clone(),
+            }),
         }
     }
 
+    /// Returns a zero-copy slice of the buffer
+    ///
+    /// Unlike [`Self::slice_with_length`], which copies owned buffers, this first puts the
+    /// buffer into borrowed mode (see [`Self::borrow_and_clone`]).  Repeatedly slicing an owned
+    /// buffer this way only converts it once and all of the slices share its allocation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `offset + length` is greater than the length of the buffer
+    pub fn slice_shared(&mut self, offset: usize, length: usize) -> Self {
+        self.borrow_and_clone().slice_with_length(offset, length)
+    }
+
+    /// Splits the buffer into two at byte offset `mid`, without copying
+    ///
+    /// The first buffer contains the bytes `[0, mid)` and the second contains `[mid, len)`.
+    /// The buffer is converted into borrowed mode first (see [`Self::into_borrowed`], which is
+    /// zero-copy) and both halves are slices sharing that allocation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mid` is greater than the length of the buffer
+    pub fn split_at(self, mid: usize) -> (Self, Self) {
+        assert!(
+            mid <= self.len(),
+            "split_at mid ({}) cannot exceed the buffer length ({})",
+            mid,
+            self.len()
+        );
+        let buffer = self.into_borrowed();
+        let second = buffer.slice_with_length(mid, buffer.len() - mid);
+        let first = buffer.slice_with_length(0, mid);
+        (first, second)
+    }
+
+    /// Iterates over non-overlapping `chunk_size`-byte chunks of the buffer
+    ///
+    /// If the length is not a multiple of `chunk_size` then the final chunk is shorter.  See
+    /// [`slice::chunks`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is 0
+    pub fn chunks(&self, chunk_size: usize) -> std::slice::Chunks<'_, u8> {
+        self.as_ref().chunks(chunk_size)
+    }
+
+    /// Iterates over non-overlapping chunks of exactly `chunk_size` bytes
+    ///
+    /// If the length is not a multiple of `chunk_size` then the trailing bytes are not yielded
+    /// and are available from [`std::slice::ChunksExact::remainder`].  See
+    /// [`slice::chunks_exact`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is 0
+    pub fn chunks_exact(&self, chunk_size: usize) -> std::slice::ChunksExact<'_, u8> {
+        self.as_ref().chunks_exact(chunk_size)
+    }
+
     // Backport of https://github.com/apache/arrow-rs/pull/6707
     fn arrow_bit_slice(
         buf: &arrow_buffer::Buffer,
@@ -406,88 +914,3041 @@ impl LanceBuffer {
     /// This means, given the bit buffer 0bABCDEFGH_HIJKLMNOP and the slice starting at bit 3 and
     /// with length 8, the result will be 0bNOPABCDE
     pub fn bit_slice_le_with_length(&mut self, offset: usize, length: usize) -> Self {
-        let Self::Borrowed(borrowed) = self.borrow_and_clone() else {
-            unreachable!()
-        };
+        let borrowed = self.borrow_and_clone().into_buffer();
         // Use this and remove backport once we upgrade to arrow-rs 54
         // let sliced = borrowed.bit_slice(offset, length);
         let sliced = Self::arrow_bit_slice(&borrowed, offset, length);
         Self::Borrowed(sliced)
     }
-}
 
-impl AsRef<[u8]> for LanceBuffer {
-    fn as_ref(&self) -> &[u8] {
-        match self {
-            Self::Borrowed(buffer) => buffer.as_slice(),
-            Self::Owned(buffer) => buffer.as_slice(),
+    /// Returns a new [LanceBuffer] that is a slice of this buffer starting at bit `offset`
+    /// with `length` bits.
+    ///
+    /// This is the same as [`Self::bit_slice_le_with_length`] except that it uses the *bitwise*
+    /// big-endian convention where bit 0 is the most significant bit of byte 0.  This is
+    /// needed when working with external bitstream formats.
+    ///
+    /// This means, given the bit buffer 0bABCDEFGH_IJKLMNOP and the slice starting at bit 3 and
+    /// with length 8, the result will be 0bDEFGHIJK
+    ///
+    /// If the offset is not byte-aligned then the data is copied and any bits past `length` in
+    /// the final byte are unset.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `offset + length` is greater than the number of bits in the buffer
+    pub fn bit_slice_be_with_length(&mut self, offset: usize, length: usize) -> Self {
+        assert!(
+            offset.saturating_add(length) <= self.len() * 8,
+            "the offset + length of the bit slice cannot exceed the existing length"
+        );
+        let borrowed = self.borrow_and_clone().into_buffer();
+        if offset % 8 == 0 {
+            return Self::Borrowed(borrowed.slice_with_length(offset / 8, length.div_ceil(8)));
         }
-    }
-}
 
-impl Deref for LanceBuffer {
-    type Target = [u8];
+        let shift = offset % 8;
+        let start = offset / 8;
+        let data = borrowed.as_slice();
+        let mut sliced = (start..start + length.div_ceil(8))
+            .map(|idx| {
+                let high = data[idx] << shift;
+                let low = data.get(idx + 1).map_or(0, |next| next >> (8 - shift));
+                high | low
+            })
+            .collect::<Vec<_>>();
+        if length % 8 != 0 {
+            if let Some(last) = sliced.last_mut() {
+                *last &= 0xFF << (8 - length % 8);
+            }
+        }
+        Self::Borrowed(Buffer::from_vec(sliced))
+    }
 
-    fn deref(&self) -> &Self::Target {
-        self.as_ref()
+    /// Returns an error if the buffer cannot be split into values of `value_width` bytes
+    fn check_value_width(&self, value_width: usize) -> Result<()> {
+        if value_width == 0 || self.len() % value_width != 0 {
+            return Err(Error::invalid_input(
+                format!(
+                    "a buffer of {} bytes cannot be interpreted as values of size {} because it is not evenly divisible",
+                    self.len(),
+                    value_width
+                ),
+                location!(),
+            ));
+        }
+        Ok(())
     }
-}
 
-// All `From` implementations are zero-copy
+    /// Views the buffer as a slice of `T` values in native-endian order
+    ///
+    /// This is zero-copy if the buffer is aligned for `T`, otherwise the values are copied
+    /// into an aligned allocation.  Returns an error if the buffer length is not a multiple
+    /// of the size of `T`.
+    fn typed_values<T: ArrowNativeType>(&self) -> Result<Cow<'_, [T]>> {
+        let value_size = std::mem::size_of::<T>();
+        self.check_value_width(value_size)?;
+        // SAFETY: ArrowNativeType is only implemented for plain-old-data types where
+        // every bit pattern is a valid value
+        let (prefix, values, suffix) = unsafe { self.as_ref().align_to::<T>() };
+        if prefix.is_empty() && suffix.is_empty() {
+            Ok(Cow::Borrowed(values))
+        } else {
+            let num_values = self.len() / value_size;
+            let mut values = Vec::<T>::with_capacity(num_values);
+            // SAFETY: the destination has capacity for exactly self.len() bytes and every
+            // bit pattern is a valid T
+            unsafe {
+                std::ptr::copy_nonoverlapping(
+                    self.as_ptr(),
+                    values.as_mut_ptr() as *mut u8,
+                    self.len(),
+                );
+                values.set_len(num_values);
+            }
+            Ok(Cow::Owned(values))
+        }
+    }
 
-impl From<Vec<u8>> for LanceBuffer {
-    fn from(buffer: Vec<u8>) -> Self {
-        Self::Owned(buffer)
+    /// Creates an owned buffer by writing each value in native-endian order
+    fn from_values<T: ArrowNativeType>(values: impl IntoIterator<Item = T>) -> Self {
+        let values = values.into_iter();
+        let mut data = Vec::with_capacity(values.size_hint().0 * std::mem::size_of::<T>());
+        for value in values {
+            data.extend_from_slice(value.to_byte_slice());
+        }
+        Self::Owned(data)
     }
-}
 
-impl From<Buffer> for LanceBuffer {
-    fn from(buffer: Buffer) -> Self {
-        Self::Borrowed(buffer)
+    /// Computes the absolute value of each `T` in the buffer, returning a new owned buffer
+    ///
+    /// The buffer is interpreted as native-endian values of `T`.  `T::MIN` has no positive
+    /// counterpart and so it wraps and is returned unchanged (the same behavior as
+    /// [`i32::wrapping_abs`]).
+    ///
+    /// Returns an error if the buffer length is not a multiple of the size of `T`.
+    pub fn abs_values<T: ArrowNativeType + PrimInt + Signed + WrappingNeg>(&self) -> Result<Self> {
+        let values = self.typed_values::<T>()?;
+        Ok(Self::from_values(values.iter().map(|&value| {
+            if value < T::zero() {
+                value.wrapping_neg()
+            } else {
+                value
+            }
+        })))
     }
-}
 
-// An iterator that keeps a clone of a borrowed LanceBuffer so we
-// can have a 'static lifetime
-pub struct BorrowedBufferIter {
-    buffer: arrow_buffer::Buffer,
-    index: usize,
-}
+    /// Negates each `T` in the buffer, returning a new owned buffer
+    ///
+    /// The buffer is interpreted as native-endian values of `T`.  Negating `T::MIN` wraps
+    /// and returns `T::MIN` (the same behavior as [`i32::wrapping_neg`]).
+    ///
+    /// Returns an error if the buffer length is not a multiple of the size of `T`.
+    pub fn neg_values<T: ArrowNativeType + PrimInt + Signed + WrappingNeg>(&self) -> Result<Self> {
+        let values = self.typed_values::<T>()?;
+        Ok(Self::from_values(
+            values.iter().map(|value| value.wrapping_neg()),
+        ))
+    }
 
-impl Iterator for BorrowedBufferIter {
-    type Item = u8;
+    /// Clamps each `T` in the buffer into the range `[min, max]`, returning a new owned buffer
+    ///
+    /// The buffer is interpreted as native-endian values of `T`.
+    ///
+    /// Returns an error if the buffer length is not a multiple of the size of `T` or if
+    /// `min` is greater than `max` (or the two cannot be compared, e.g. NaN)
+    pub fn clamp_values<T: ArrowNativeType + PartialOrd>(&self, min: T, max: T) -> Result<Self> {
+        if matches!(
+            min.partial_cmp(&max),
+            None | Some(std::cmp::Ordering::Greater)
+        ) {
+            return Err(Error::invalid_input(
+                format!(
+                    "clamp_values requires min <= max but received min={:?} and max={:?}",
+                    min, max
+                ),
+                location!(),
+            ));
+        }
+        let values = self.typed_values::<T>()?;
+        Ok(Self::from_values(values.iter().map(|&value| {
+            if value < min {
+                min
+            } else if value > max {
+                max
+            } else {
+                value
+            }
+        })))
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.index >= self.buffer.len() {
-            None
-        } else {
-            // SAFETY: we just checked that index is in bounds
-            let byte = unsafe { self.buffer.get_unchecked(self.index) };
-            self.index += 1;
-            Some(*byte)
+    /// Interleaves the bits of two `u32` buffers into a buffer of `u64` Morton codes
+    ///
+    /// Bit `i` of each `x` value becomes bit `2 * i` of the output and bit `i` of each `y`
+    /// value becomes bit `2 * i + 1`.  This ordering keeps points that are close together
+    /// in 2D space close together in the output which is useful for spatial indexing.
+    ///
+    /// Returns an error if the buffers are not valid `u32` buffers or do not contain the same
+    /// number of values.
+    pub fn morton_encode_u32(x: &Self, y: &Self) -> Result<Self> {
+        let x = x.typed_values::<u32>()?;
+        let y = y.typed_values::<u32>()?;
+        if x.len() != y.len() {
+            return Err(Error::invalid_input(
+                format!(
+                    "morton_encode_u32 requires buffers with the same number of values but received {} and {}",
+                    x.len(),
+                    y.len()
+                ),
+                location!(),
+            ));
         }
+        Ok(Self::from_values(x.iter().zip(y.iter()).map(|(&x, &y)| {
+            spread_bits_u32(x) | (spread_bits_u32(y) << 1)
+        })))
     }
-}
 
-impl IntoIterator for LanceBuffer {
-    type Item = u8;
-    type IntoIter = Either<std::vec::IntoIter<u8>, BorrowedBufferIter>;
+    /// The inverse of [`Self::morton_encode_u32`], splits a buffer of `u64` Morton codes
+    /// into `(x, y)` buffers of `u32` values
+    ///
+    /// Returns an error if the buffer length is not a multiple of 8.
+    pub fn morton_decode_u64(&self) -> Result<(Self, Self)> {
+        let codes = self.typed_values::<u64>()?;
+        let x = Self::from_values(codes.iter().map(|&code| compact_bits_u64(code)));
+        let y = Self::from_values(codes.iter().map(|&code| compact_bits_u64(code >> 1)));
+        Ok((x, y))
+    }
 
-    fn into_iter(self) -> Self::IntoIter {
-        match self {
-            Self::Borrowed(buffer) => Either::Right(BorrowedBufferIter { buffer, index: 0 }),
-            Self::Owned(buffer) => Either::Left(buffer.into_iter()),
+    /// Interleaves the low `bits_per_dim` bits of each of the `u16` buffers in `buffers` into
+    /// a buffer of `u64` Morton codes
+    ///
+    /// This generalizes [`Self::morton_encode_u32`] to more dimensions (e.g. 3D or 4D keys).
+    /// Bit `i` of a value in dimension `d` becomes bit `i * buffers.len() + d` of the output.
+    ///
+    /// Returns an error if there are no buffers, if `bits_per_dim` is not in `1..=16`, if
+    /// `buffers.len() * bits_per_dim` exceeds 64, if the buffers are not valid `u16` buffers
+    /// with the same number of values, or if any value does not fit in `bits_per_dim` bits.
+    pub fn morton_encode_nway(buffers: &[Self], bits_per_dim: u8) -> Result<Self> {
+        Self::check_morton_nway(buffers.len(), bits_per_dim)?;
+        let dims = buffers
+            .iter()
+            .map(|buffer| buffer.typed_values::<u16>())
+            .collect::<Result<Vec<_>>>()?;
+        let num_values = dims[0].len();
+        if let Some(mismatch) = dims.iter().find(|dim| dim.len() != num_values) {
+            return Err(Error::invalid_input(
+                format!(
+                    "morton_encode_nway requires buffers with the same number of values but received {} and {}",
+                    num_values,
+                    mismatch.len()
+                ),
+                location!(),
+            ));
+        }
+        let num_dims = dims.len();
+        let mut codes = vec![0_u64; num_values];
+        for (dim_idx, dim) in dims.iter().enumerate() {
+            for (value_idx, (code, &value)) in codes.iter_mut().zip(dim.iter()).enumerate() {
+                if bits_per_dim < 16 && value >> bits_per_dim != 0 {
+                    return Err(Error::invalid_input(
+                        format!(
+                            "value {} at index {} of dimension {} does not fit in {} bits",
+                            value, value_idx, dim_idx, bits_per_dim
+                        ),
+                        location!(),
+                    ));
+                }
+                for bit in 0..bits_per_dim as usize {
+                    *code |= (((value >> bit) & 1) as u64) << (bit * num_dims + dim_idx);
+                }
+            }
         }
+        Ok(Self::reinterpret_vec(codes))
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use arrow_buffer::Buffer;
+    /// The inverse of [`Self::morton_encode_nway`], splits a buffer of `u64` Morton codes
+    /// into `num_dims` buffers of `u16` values
+    ///
+    /// Returns an error if the buffer length is not a multiple of 8 or if `num_dims` and
+    /// `bits_per_dim` are not valid (see [`Self::morton_encode_nway`]).
+    pub fn morton_decode_nway(&self, num_dims: usize, bits_per_dim: u8) -> Result<Vec<Self>> {
+        Self::check_morton_nway(num_dims, bits_per_dim)?;
+        let codes = self.typed_values::<u64>()?;
+        Ok((0..num_dims)
+            .map(|dim_idx| {
+                Self::from_values(codes.iter().map(|&code| {
+                    (0..bits_per_dim as usize).fold(0_u16, |value, bit| {
+                        value | ((((code >> (bit * num_dims + dim_idx)) & 1) as u16) << bit)
+                    })
+                }))
+            })
+            .collect())
+    }
 
-    use super::LanceBuffer;
+    fn check_morton_nway(num_dims: usize, bits_per_dim: u8) -> Result<()> {
+        if num_dims == 0
+            || !(1..=16).contains(&bits_per_dim)
+            || num_dims * bits_per_dim as usize > 64
+        {
+            return Err(Error::invalid_input(
+                format!(
+                    "cannot interleave {} dimensions of {} bits into a 64-bit Morton code",
+                    num_dims, bits_per_dim
+                ),
+                location!(),
+            ));
+        }
+        Ok(())
+    }
 
-    #[test]
+    /// Converts the buffer into an Arrow [`FixedSizeBinaryArray`] where each value is
+    /// `value_width` bytes
+    ///
+    /// This is zero-copy, see [`Self::into_buffer`].  The resulting array has no nulls.
+    ///
+    /// Returns an error if `value_width` is not positive or the buffer length is not a
+    /// multiple of `value_width`.
+    pub fn into_fixed_size_binary(self, value_width: i32) -> Result<FixedSizeBinaryArray> {
+        if value_width <= 0 || self.len() % value_width as usize != 0 {
+            return Err(Error::invalid_input(
+                format!(
+                    "cannot create a fixed size binary array with value width {} from a buffer of {} bytes",
+                    value_width,
+                    self.len()
+                ),
+                location!(),
+            ));
+        }
+        Ok(FixedSizeBinaryArray::try_new(
+            value_width,
+            self.into_buffer(),
+            None,
+        )?)
+    }
+
+    /// Switches the buffer into owned mode and returns a mutable reference to the data
+    ///
+    /// If the buffer is borrowed then we try to reclaim the allocation, which is only possible
+    /// if this is the sole reference to it.  Otherwise (or if the buffer is shared) the data is
+    /// copied.
+    fn owned_mut(&mut self) -> &mut Vec<u8> {
+        match self {
+            Self::Borrowed(buffer) => {
+                let buffer = std::mem::replace(buffer, Buffer::from_vec(Vec::<u8>::new()));
+                let data = buffer
+                    .into_vec::<u8>()
+                    .unwrap_or_else(|buffer| buffer.to_vec());
+                *self = Self::Owned(data);
+            }
+            Self::Shared(buffer) => *self = Self::Owned(buffer.to_vec()),
+            #[cfg(feature = "mmap")]
+            Self::Mapped(mapped) => *self = Self::Owned(mapped.buffer.to_vec()),
+            Self::Owned(_) => {}
+        }
+        match self {
+            Self::Owned(data) => data,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Returns a mutable slice of the buffer's bytes, copying the data first if needed
+    ///
+    /// Like [`Arc::make_mut`], an owned buffer is returned directly.  A borrowed buffer that
+    /// is the only reference to its allocation takes over that allocation without copying.
+    /// Otherwise (the allocation is shared with clones, or the buffer is `Shared`) the data is
+    /// copied into a new owned buffer so no other buffer sees the changes.
+    pub fn make_mut(&mut self) -> &mut [u8] {
+        self.owned_mut().as_mut_slice()
+    }
+
+    /// Appends a value to the end of the buffer in native-endian order
+    pub fn push_value<T: ArrowNativeType>(&mut self, value: T) {
+        self.owned_mut().extend_from_slice(value.to_byte_slice());
+    }
+
+    /// Concatenates validity bitmaps from several chunks into a single bitmap
+    ///
+    /// Each part is a bitmap and its number of bits, `None` means the chunk is entirely valid.
+    /// Returns the combined bitmap and its number of bits, or `None` if every part is `None`.
+    pub fn concat_optional_bitmaps(parts: &[(Option<&Self>, usize)]) -> Option<(Self, usize)> {
+        if parts.iter().all(|(bitmap, _)| bitmap.is_none()) {
+            return None;
+        }
+        let num_bits = parts.iter().map(|(_, num_bits)| num_bits).sum();
+        let mut builder = BooleanBufferBuilder::new(num_bits);
+        for (bitmap, num_bits) in parts {
+            match bitmap {
+                Some(bitmap) => builder.append_packed_range(0..*num_bits, bitmap),
+                None => builder.append_n(*num_bits, true),
+            }
+        }
+        Some((Self::Borrowed(builder.finish().into_inner()), num_bits))
+    }
+
+    /// Rotates the bytes of the buffer `n` places to the left (the first `n % len` bytes
+    /// are moved to the end)
+    pub fn rotate_bytes_left(&mut self, n: usize) {
+        if self.is_empty() {
+            return;
+        }
+        let n = n % self.len();
+        self.owned_mut().rotate_left(n);
+    }
+
+    /// Rotates the bytes of the buffer `n` places to the right (the last `n % len` bytes
+    /// are moved to the front)
+    pub fn rotate_bytes_right(&mut self, n: usize) {
+        if self.is_empty() {
+            return;
+        }
+        let n = n % self.len();
+        self.owned_mut().rotate_right(n);
+    }
+
+    /// Returns true if the buffer appears compressible
+    ///
+    /// This computes the Shannon entropy (in bits per byte, between 0 and 8) of a sample of
+    /// the buffer and returns true if it is below `entropy_threshold`.  To keep this cheap on
+    /// large buffers only the first, middle, and last 4KiB are sampled.
+    ///
+    /// This is only a heuristic.  Entropy does not account for repeated sequences and so some
+    /// compressible data (e.g. a repeating pattern of all 256 byte values) will not be detected.
+    pub fn should_compress(&self, entropy_threshold: f64) -> bool {
+        if self.is_empty() {
+            return false;
+        }
+        let mut histogram = [0_u64; 256];
+        let mut num_sampled = 0;
+        let mut sample = |bytes: &[u8]| {
+            for &byte in bytes {
+                histogram[byte as usize] += 1;
+            }
+            num_sampled += bytes.len();
+        };
+        if self.len() <= ENTROPY_SAMPLE_BYTES * 3 {
+            sample(self);
+        } else {
+            let middle = (self.len() - ENTROPY_SAMPLE_BYTES) / 2;
+            sample(&self[..ENTROPY_SAMPLE_BYTES]);
+            sample(&self[middle..middle + ENTROPY_SAMPLE_BYTES]);
+            sample(&self[self.len() - ENTROPY_SAMPLE_BYTES..]);
+        }
+        let num_sampled = num_sampled as f64;
+        let entropy = histogram
+            .iter()
+            .filter(|&&count| count > 0)
+            .map(|&count| {
+                let probability = count as f64 / num_sampled;
+                -probability * probability.log2()
+            })
+            .sum::<f64>();
+        entropy < entropy_threshold
+    }
+
+    /// Ensures the values in the buffer are stored in little-endian order
+    ///
+    /// The buffer is assumed to contain native-endian values of `T`.  On little-endian targets
+    /// this is a no-op, on big-endian targets each value is byte-swapped.
+    ///
+    /// # Panics
+    ///
+    /// On big-endian targets, panics if the buffer length is not a multiple of the size of `T`
+    pub fn ensure_little_endian<T: ArrowNativeType>(&mut self) {
+        #[cfg(target_endian = "big")]
+        {
+            let value_size = std::mem::size_of::<T>();
+            assert_eq!(
+                self.len() % value_size,
+                0,
+                "ensure_little_endian requires a buffer length that is a multiple of the value size"
+            );
+            for value in self.owned_mut().chunks_exact_mut(value_size) {
+                value.reverse();
+            }
+        }
+    }
+
+    /// Reverses the byte order of each `T`-sized value in the buffer, in place
+    ///
+    /// This converts values between big-endian and little-endian.
+    ///
+    /// Returns an error if the buffer length is not a multiple of the size of `T`.
+    pub fn swap_bytes_in_place<T: ArrowNativeType>(&mut self) -> Result<()> {
+        let value_size = std::mem::size_of::<T>();
+        self.check_value_width(value_size)?;
+        for value in self.owned_mut().chunks_exact_mut(value_size) {
+            value.reverse();
+        }
+        Ok(())
+    }
+
+    /// Returns a new owned buffer with the byte order of each `T`-sized value reversed
+    ///
+    /// See [`Self::swap_bytes_in_place`]
+    pub fn to_endian_swapped<T: ArrowNativeType>(&self) -> Result<Self> {
+        let value_size = std::mem::size_of::<T>();
+        self.check_value_width(value_size)?;
+        Ok(Self::Owned(
+            self.chunks_exact(value_size)
+                .flat_map(|value| value.iter().rev().copied())
+                .collect(),
+        ))
+    }
+
+    /// Applies `f` to each `chunk_size`-byte chunk of the buffer, mutating it in place
+    ///
+    /// If the buffer length is not a multiple of `chunk_size` then the final chunk is shorter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is 0
+    pub fn map_chunks_in_place(&mut self, chunk_size: usize, mut f: impl FnMut(&mut [u8])) {
+        for chunk in self.owned_mut().chunks_mut(chunk_size) {
+            f(chunk);
+        }
+    }
+
+    /// Returns the positions of the set bits in the first `len_bits` bits of a bitmap
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len_bits` is greater than the number of bits in the buffer
+    pub fn set_bit_indices(&self, len_bits: usize) -> Vec<u32> {
+        assert!(
+            len_bits <= self.len() * 8,
+            "set_bit_indices called with {} bits on a buffer of {} bytes",
+            len_bits,
+            self.len()
+        );
+        BitIndexIterator::new(self, 0, len_bits)
+            .map(|index| index as u32)
+            .collect()
+    }
+
+    /// Packs `bits` into a new owned bitmap, any padding bits in the final byte are unset
+    pub fn from_bools(bits: &[bool]) -> Self {
+        let mut data = vec![0; bits.len().div_ceil(8)];
+        for (index, _) in bits.iter().enumerate().filter(|(_, &bit)| bit) {
+            bit_util::set_bit(&mut data, index);
+        }
+        Self::Owned(data)
+    }
+
+    /// Unpacks the first `num_bits` bits of the buffer into bools
+    ///
+    /// This is the inverse of [`Self::from_bools`], any bits past `num_bits` are ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num_bits` is greater than the number of bits in the buffer
+    pub fn to_bools(&self, num_bits: usize) -> Vec<bool> {
+        assert!(
+            num_bits <= self.len() * 8,
+            "to_bools called with {} bits on a buffer of {} bytes",
+            num_bits,
+            self.len()
+        );
+        (0..num_bits)
+            .map(|index| bit_util::get_bit(self, index))
+            .collect()
+    }
+
+    /// Returns bit `i` of a bitmap
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is not less than the number of bits in the buffer
+    pub fn get_bit(&self, i: usize) -> bool {
+        assert!(
+            i < self.len() * 8,
+            "get_bit called with bit {} on a buffer of {} bytes",
+            i,
+            self.len()
+        );
+        bit_util::get_bit(self, i)
+    }
+
+    /// Sets bit `i` of a bitmap to `value`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is not less than the number of bits in the buffer
+    pub fn set_bit(&mut self, i: usize, value: bool) {
+        assert!(
+            i < self.len() * 8,
+            "set_bit called with bit {} on a buffer of {} bytes",
+            i,
+            self.len()
+        );
+        let data = self.owned_mut();
+        if value {
+            bit_util::set_bit(data, i);
+        } else {
+            bit_util::unset_bit(data, i);
+        }
+    }
+
+    /// Concatenates multiple buffers into a single buffer, ignoring any empty buffers
+    ///
+    /// If only one non-empty buffer remains it is returned as is (if it is borrowed then
+    /// no copy is made).  Otherwise this is the same as [`Self::concat_into_one`].
+    pub fn concat_skipping_empty(buffers: Vec<Self>) -> Self {
+        let mut buffers = buffers
+            .into_iter()
+            .filter(|buffer| !buffer.is_empty())
+            .collect::<Vec<_>>();
+        if buffers.is_empty() {
+            Self::empty()
+        } else if buffers.len() == 1 {
+            buffers.pop().unwrap()
+        } else {
+            Self::concat_into_one(buffers)
+        }
+    }
+
+    /// Reverses the order of the bits within each byte, returning a new owned buffer
+    ///
+    /// This converts between bitmaps that store the first bit in the most significant bit of
+    /// each byte and the Arrow convention which stores it in the least significant bit.  For
+    /// example, `0b0000_0001` becomes `0b1000_0000`.
+    pub fn reverse_bits_per_byte(&self) -> Self {
+        Self::Owned(
+            self.iter()
+                .map(|&byte| BIT_REVERSE_TABLE[byte as usize])
+                .collect(),
+        )
+    }
+
+    /// Grows a bitmap from `old_len_bits` to `new_len_bits`, setting each of the new bits
+    /// to `fill`
+    ///
+    /// The buffer is zero-padded if it is too short to hold `new_len_bits`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `new_len_bits` is less than `old_len_bits` or if `old_len_bits` is greater
+    /// than the number of bits in the buffer
+    pub fn grow_bitmap(&mut self, old_len_bits: usize, new_len_bits: usize, fill: bool) {
+        assert!(
+            old_len_bits <= new_len_bits,
+            "grow_bitmap cannot shrink a bitmap from {} to {} bits",
+            old_len_bits,
+            new_len_bits
+        );
+        assert!(
+            old_len_bits <= self.len() * 8,
+            "grow_bitmap called with {} bits on a buffer of {} bytes",
+            old_len_bits,
+            self.len()
+        );
+        let data = self.owned_mut();
+        let num_bytes = new_len_bits.div_ceil(8);
+        if data.len() < num_bytes {
+            data.resize(num_bytes, 0);
+        }
+        let fill_byte = if fill { 0xFF } else { 0x00 };
+        let mut bit = old_len_bits;
+        while bit < new_len_bits {
+            if bit % 8 == 0 && bit + 8 <= new_len_bits {
+                // Fill whole bytes at once
+                let end_byte = new_len_bits / 8;
+                data[bit / 8..end_byte].fill(fill_byte);
+                bit = end_byte * 8;
+            } else {
+                if fill {
+                    bit_util::set_bit(data, bit);
+                } else {
+                    bit_util::unset_bit(data, bit);
+                }
+                bit += 1;
+            }
+        }
+    }
+
+    /// Returns the byte at `index` or `None` if `index` is out of bounds
+    ///
+    /// Unlike indexing (via [`Deref`]) this never panics which is useful when parsing
+    /// untrusted data.
+    pub fn get(&self, index: usize) -> Option<u8> {
+        self.as_ref().get(index).copied()
+    }
+
+    /// Returns the bytes in `range` or `None` if the range is out of bounds
+    pub fn get_range(&self, range: Range<usize>) -> Option<&[u8]> {
+        self.as_ref().get(range)
+    }
+
+    /// Estimates the size of the buffer after zstd compression at the given `level`
+    ///
+    /// Rather than compressing the entire buffer this compresses a sample of roughly
+    /// `sample_bytes` bytes (several evenly spaced windows of the buffer) and extrapolates
+    /// the compression ratio to the full length.  If the buffer is no larger than
+    /// `sample_bytes` then the entire buffer is compressed and the result is exact.
+    ///
+    /// This is only an estimate and can be inaccurate if the sampled windows are not
+    /// representative of the rest of the data.  If compression fails the uncompressed length
+    /// is returned.
+    #[cfg(feature = "zstd")]
+    pub fn estimate_compressed_size_zstd(&self, level: i32, sample_bytes: usize) -> usize {
+        if self.is_empty() {
+            return 0;
+        }
+        let sample = if sample_bytes >= self.len() || sample_bytes == 0 {
+            Cow::Borrowed(self.as_ref())
+        } else {
+            let window_size = sample_bytes.div_ceil(COMPRESSION_SAMPLE_WINDOWS);
+            let stride = self.len() / COMPRESSION_SAMPLE_WINDOWS;
+            let mut sample = Vec::with_capacity(window_size * COMPRESSION_SAMPLE_WINDOWS);
+            for window_idx in 0..COMPRESSION_SAMPLE_WINDOWS {
+                let start = window_idx * stride;
+                let end = (start + window_size).min(self.len());
+                sample.extend_from_slice(&self[start..end]);
+            }
+            Cow::Owned(sample)
+        };
+        match zstd::bulk::compress(&sample, level) {
+            Ok(compressed) => {
+                let ratio = compressed.len() as f64 / sample.len() as f64;
+                (ratio * self.len() as f64).round() as usize
+            }
+            Err(_) => self.len(),
+        }
+    }
+
+    /// Compresses the buffer with zstd at the given `level`, returning a new owned buffer
+    ///
+    /// The output is a single zstd frame.  Use [`Self::decompress_zstd`] to decompress it.
+    #[cfg(feature = "zstd")]
+    pub fn compress_zstd(&self, level: i32) -> Result<Self> {
+        Ok(Self::Owned(zstd::bulk::compress(self, level)?))
+    }
+
+    /// Decompresses a zstd compressed buffer into a new owned buffer of exactly
+    /// `decompressed_len` bytes
+    ///
+    /// If the data is a single zstd frame that records its content size (as written by
+    /// [`Self::compress_zstd`]) then `decompressed_len` is checked against that size before
+    /// anything is allocated, so a corrupt length is reported as an error rather than
+    /// triggering a huge allocation, and the output is then allocated up front.  Otherwise the
+    /// data is decompressed incrementally and no more than `decompressed_len + 1` bytes are
+    /// ever decompressed.
+    ///
+    /// Returns an error if the data is not valid zstd (e.g. it was truncated) or if it does not
+    /// decompress to exactly `decompressed_len` bytes.
+    #[cfg(feature = "zstd")]
+    pub fn decompress_zstd(&self, decompressed_len: usize) -> Result<Self> {
+        Self::decompress_zstd_slice(self, decompressed_len).map(Self::Owned)
+    }
+
+    #[cfg(feature = "zstd")]
+    fn decompress_zstd_slice(data: &[u8], decompressed_len: usize) -> Result<Vec<u8>> {
+        let invalid = |err: &dyn std::fmt::Display| {
+            Error::invalid_input(
+                format!("the buffer could not be decompressed with zstd: {}", err),
+                location!(),
+            )
+        };
+        let wrong_len = |actual_len: u64| {
+            Error::invalid_input(
+                format!(
+                    "the buffer decompressed to {} bytes instead of the expected {} bytes",
+                    actual_len, decompressed_len
+                ),
+                location!(),
+            )
+        };
+        // The up-front allocation is only trusted when the data is a single frame that records
+        // its content size, everything else (e.g. multiple frames) is decompressed incrementally
+        let single_frame = zstd::zstd_safe::find_frame_compressed_size(data) == Ok(data.len());
+        let decompressed = match zstd::zstd_safe::get_frame_content_size(data) {
+            Ok(Some(recorded_len)) if single_frame => {
+                if recorded_len != decompressed_len as u64 {
+                    return Err(wrong_len(recorded_len));
+                }
+                zstd::bulk::decompress(data, decompressed_len).map_err(|err| invalid(&err))?
+            }
+            Ok(_) => {
+                use std::io::Read;
+                let mut decompressed = Vec::new();
+                zstd::stream::read::Decoder::new(data)
+                    .and_then(|decoder| {
+                        decoder
+                            .take((decompressed_len as u64).saturating_add(1))
+                            .read_to_end(&mut decompressed)
+                    })
+                    .map_err(|err| invalid(&err))?;
+                decompressed
+            }
+            Err(err) => return Err(invalid(&err)),
+        };
+        if decompressed.len() != decompressed_len {
+            return Err(wrong_len(decompressed.len() as u64));
+        }
+        Ok(decompressed)
+    }
+
+    /// Compresses the buffer into a raw LZ4 block, returning a new owned buffer
+    ///
+    /// The block does not record the uncompressed size and so it must be stored separately
+    /// and passed to [`Self::decompress_lz4`].
+    #[cfg(feature = "lz4")]
+    pub fn compress_lz4(&self) -> Result<Self> {
+        Ok(Self::Owned(lz4::block::compress(self, None, false)?))
+    }
+
+    /// Decompresses a raw LZ4 block into a new owned buffer of exactly `decompressed_len` bytes
+    ///
+    /// The data is decompressed directly into a preallocated buffer, no intermediate copy is
+    /// made.  Before allocating, `decompressed_len` is checked against the largest output an LZ4
+    /// block of this size could produce so a corrupt length cannot trigger a huge allocation.
+    /// Returns an error if the length fails that check, if the data is not a valid LZ4 block
+    /// (e.g. it was truncated or corrupted), or if it does not decompress to exactly
+    /// `decompressed_len` bytes.
+    #[cfg(feature = "lz4")]
+    pub fn decompress_lz4(&self, decompressed_len: usize) -> Result<Self> {
+        Self::decompress_lz4_slice(self, decompressed_len).map(Self::Owned)
+    }
+
+    #[cfg(feature = "lz4")]
+    fn decompress_lz4_slice(data: &[u8], decompressed_len: usize) -> Result<Vec<u8>> {
+        if decompressed_len > data.len().saturating_mul(LZ4_MAX_COMPRESSION_RATIO) {
+            return Err(Error::invalid_input(
+                format!(
+                    "cannot decompress {} bytes from an lz4 block of only {} bytes",
+                    decompressed_len,
+                    data.len()
+                ),
+                location!(),
+            ));
+        }
+        let size = i32::try_from(decompressed_len).map_err(|_| {
+            Error::invalid_input(
+                format!(
+                    "cannot decompress {} bytes, LZ4 blocks are limited to {} bytes",
+                    decompressed_len,
+                    i32::MAX
+                ),
+                location!(),
+            )
+        })?;
+        let mut decompressed = vec![0; decompressed_len];
+        let written = lz4::block::decompress_to_buffer(data, Some(size), &mut decompressed)
+            .map_err(|err| {
+                Error::invalid_input(
+                    format!("the buffer could not be decompressed with lz4: {}", err),
+                    location!(),
+                )
+            })?;
+        if written != decompressed_len {
+            return Err(Error::invalid_input(
+                format!(
+                    "the buffer decompressed to {} bytes instead of the expected {} bytes",
+                    written, decompressed_len
+                ),
+                location!(),
+            ));
+        }
+        Ok(decompressed)
+    }
+
+    /// Counts the number of positions at which the bytes of `self` and `other` differ
+    ///
+    /// The buffers are compared 8 bytes at a time so that identical regions are skipped
+    /// quickly.  Returns an error if the buffers have different lengths.
+    pub fn byte_diff_count(&self, other: &Self) -> Result<usize> {
+        if self.len() != other.len() {
+            return Err(Error::invalid_input(
+                format!(
+                    "byte_diff_count requires buffers of the same length but received {} and {} bytes",
+                    self.len(),
+                    other.len()
+                ),
+                location!(),
+            ));
+        }
+        let left = self.chunks_exact(8);
+        let right = other.chunks_exact(8);
+        let tail_count = left
+            .remainder()
+            .iter()
+            .zip(right.remainder())
+            .filter(|(l, r)| l != r)
+            .count();
+        let word_count = left
+            .zip(right)
+            .map(|(l, r)| {
+                let diff = u64::from_ne_bytes(l.try_into().unwrap())
+                    ^ u64::from_ne_bytes(r.try_into().unwrap());
+                if diff == 0 {
+                    0
+                } else {
+                    diff.to_ne_bytes().iter().filter(|&&byte| byte != 0).count()
+                }
+            })
+            .sum::<usize>();
+        Ok(word_count + tail_count)
+    }
+
+    /// Returns a mutable view of the buffer as a slice of `T`
+    ///
+    /// If the owned data is not aligned for `T` then it is first copied into an aligned
+    /// allocation.
+    ///
+    /// Returns an error if the buffer length is not a multiple of the size of `T` or if the data
+    /// still cannot be aligned.
+    pub fn as_mut_typed_slice<T: ArrowNativeType>(&mut self) -> Result<&mut [T]> {
+        self.check_value_width(std::mem::size_of::<T>())?;
+        let data = self.owned_mut();
+        if data.as_ptr().align_offset(std::mem::align_of::<T>()) != 0 {
+            *data = data.as_slice().to_vec();
+        }
+        // SAFETY: ArrowNativeType is only implemented for plain-old-data types where
+        // every bit pattern is a valid value
+        let (prefix, values, suffix) = unsafe { data.align_to_mut::<T>() };
+        if prefix.is_empty() && suffix.is_empty() {
+            Ok(values)
+        } else {
+            Err(Error::Internal {
+                message: format!(
+                    "could not allocate a buffer aligned to {} bytes",
+                    std::mem::align_of::<T>()
+                ),
+                location: location!(),
+            })
+        }
+    }
+
+    /// Gathers several byte ranges, given as `(offset, length)` pairs, into a new owned buffer
+    ///
+    /// The ranges are copied in the order given and may overlap.  Returns an error if any
+    /// range extends past the end of the buffer.
+    pub fn take_ranges(&self, ranges: &[(usize, usize)]) -> Result<Self> {
+        let mut total_len = 0_usize;
+        for &(offset, length) in ranges {
+            if offset.saturating_add(length) > self.len() {
+                return Err(Error::invalid_input(
+                    format!(
+                        "take_ranges received the range (offset={}, length={}) which is out of bounds for a buffer of {} bytes",
+                        offset,
+                        length,
+                        self.len()
+                    ),
+                    location!(),
+                ));
+            }
+            total_len += length;
+        }
+        let mut data = Vec::with_capacity(total_len);
+        for &(offset, length) in ranges {
+            data.extend_from_slice(&self[offset..offset + length]);
+        }
+        Ok(Self::Owned(data))
+    }
+
+    /// Gathers fixed-width values by index into a new owned buffer
+    ///
+    /// The buffer is treated as values of `bytes_per_value` bytes each and value `i` of the
+    /// output is value `indices[i]` of the input.  Indices may repeat and appear in any order.
+    ///
+    /// Returns an error if `bytes_per_value` is 0, the buffer length is not a multiple of
+    /// `bytes_per_value`, or any index is out of bounds.
+    pub fn take_fixed_width(&self, indices: &[u32], bytes_per_value: usize) -> Result<Self> {
+        self.check_value_width(bytes_per_value)?;
+        let num_values = self.len() / bytes_per_value;
+        if let Some((position, index)) = indices
+            .iter()
+            .enumerate()
+            .find(|(_, &index)| index as usize >= num_values)
+        {
+            return Err(Error::invalid_input(
+                format!(
+                    "take_fixed_width received the index {} (at position {}) which is out of bounds for a buffer of {} values",
+                    index, position, num_values
+                ),
+                location!(),
+            ));
+        }
+        let mut data = Vec::with_capacity(indices.len() * bytes_per_value);
+        for &index in indices {
+            let start = index as usize * bytes_per_value;
+            data.extend_from_slice(&self[start..start + bytes_per_value]);
+        }
+        Ok(Self::Owned(data))
+    }
+
+    /// Keeps the fixed-width values selected by `mask`, returning a new owned buffer
+    ///
+    /// The buffer is treated as values of `bytes_per_value` bytes each and value `i` is kept if
+    /// bit `i` of the `mask` bitmap is set.
+    ///
+    /// Returns an error if `bytes_per_value` is 0, the buffer length is not a multiple of
+    /// `bytes_per_value`, or the mask has fewer bits than there are values.
+    pub fn filter_fixed_width(&self, mask: &Self, bytes_per_value: usize) -> Result<Self> {
+        self.check_value_width(bytes_per_value)?;
+        let num_values = self.len() / bytes_per_value;
+        if mask.len() * 8 < num_values {
+            return Err(Error::invalid_input(
+                format!(
+                    "filter_fixed_width received a mask of {} bits which is too short for a buffer of {} values",
+                    mask.len() * 8,
+                    num_values
+                ),
+                location!(),
+            ));
+        }
+        let num_selected = mask.count_ones_range(0, num_values) as usize;
+        let mut data = Vec::with_capacity(num_selected * bytes_per_value);
+        for index in BitIndexIterator::new(mask.as_ref(), 0, num_values) {
+            let start = index * bytes_per_value;
+            data.extend_from_slice(&self[start..start + bytes_per_value]);
+        }
+        Ok(Self::Owned(data))
+    }
+
+    /// Converts the buffer into an iterator over native-endian values of `T`
+    ///
+    /// This is zero-copy if the buffer is aligned for `T` (see [`Self::borrow_to_typed_slice`]).
+    /// The iterator can also be consumed from the back and knows its exact length.
+    ///
+    /// Returns an error if the buffer length is not a multiple of the size of `T`.
+    pub fn into_typed_iter<T: ArrowNativeType>(mut self) -> Result<TypedBufferIter<T>> {
+        self.check_value_width(std::mem::size_of::<T>())?;
+        let values = self.borrow_to_typed_slice::<T>();
+        let end = values.len();
+        Ok(TypedBufferIter {
+            values,
+            start: 0,
+            end,
+        })
+    }
+
+    /// Computes the CRC32C (Castagnoli) checksum of the buffer
+    ///
+    /// This is computed directly over the bytes of the buffer, no copy is made.
+    #[cfg(feature = "checksum")]
+    pub fn crc32c(&self) -> u32 {
+        crc32c::crc32c(self.as_ref())
+    }
+
+    /// Computes a fast, non-cryptographic 64-bit hash of the contents of the buffer
+    ///
+    /// This only depends on the bytes of the buffer (and not on whether it is owned, borrowed,
+    /// or shared) and so buffers that compare equal have the same hash.  The hash uses fixed
+    /// seeds and so it is deterministic within a process, which makes it suitable for finding
+    /// duplicate payloads in a cache.  It is not guaranteed to be stable across versions of
+    /// the hashing library and so should not be persisted.
+    #[cfg(feature = "hash")]
+    pub fn content_hash(&self) -> u64 {
+        ahash::RandomState::with_seeds(
+            0x243F_6A88_85A3_08D3,
+            0x1319_8A2E_0370_7344,
+            0xA409_3822_299F_31D0,
+            0x082E_FA98_EC4E_6C89,
+        )
+        .hash_one(self.as_ref())
+    }
+
+    /// Concatenates multiple buffers into a single buffer and computes the CRC32C checksum
+    /// of the result
+    ///
+    /// The checksum is computed incrementally as each buffer is copied so the data is only
+    /// read once.
+    #[cfg(feature = "checksum")]
+    pub fn concat_and_crc32c(buffers: &[Self]) -> (Self, u32) {
+        let total_len = buffers.iter().map(|b| b.len()).sum();
+        let mut data = Vec::with_capacity(total_len);
+        let mut crc = 0;
+        for buffer in buffers {
+            crc = crc32c::crc32c_append(crc, buffer);
+            data.extend_from_slice(buffer);
+        }
+        (Self::Owned(data), crc)
+    }
+
+    /// Splits the buffer into the pieces between each occurrence of `delimiter`
+    ///
+    /// The delimiters are not included in the output.  This has the same semantics as
+    /// [`slice::split`], a leading or trailing delimiter produces an empty piece and a buffer
+    /// with no delimiters produces a single piece.
+    ///
+    /// If the buffer is borrowed then the pieces are zero-copy slices (see
+    /// [`Self::slice_with_length`]).
+    pub fn split_by_delimiter(&self, delimiter: u8) -> Vec<Self> {
+        let mut pieces = Vec::new();
+        let mut start = 0;
+        for position in memchr::memchr_iter(delimiter, self) {
+            pieces.push(self.slice_with_length(start, position - start));
+            start = position + 1;
+        }
+        pieces.push(self.slice_with_length(start, self.len() - start));
+        pieces
+    }
+
+    /// Describes the buffer as a JSON value, for embedding in metadata
+    ///
+    /// If the buffer is no longer than `inline_limit` bytes then the contents are inlined as
+    /// `{"inline": "<hex>"}`.  Otherwise the buffer is expected to be stored elsewhere and
+    /// only a reference is returned, `{"len": <bytes>, "crc32c": <checksum>}`, which can be used
+    /// to validate the externally stored copy.
+    #[cfg(feature = "json")]
+    pub fn to_json_value(&self, inline_limit: usize) -> serde_json::Value {
+        if self.len() <= inline_limit {
+            serde_json::json!({ "inline": self.as_hex() })
+        } else {
+            serde_json::json!({ "len": self.len(), "crc32c": crc32c::crc32c(self) })
+        }
+    }
+
+    /// Reserves capacity for exactly `additional` more bytes, see [`Vec::reserve_exact`]
+    pub fn reserve_exact(&mut self, additional: usize) {
+        self.owned_mut().reserve_exact(additional);
+    }
+
+    /// Tries to reserve capacity for at least `additional` more bytes
+    ///
+    /// Returns an error instead of aborting if the allocation fails, see [`Vec::try_reserve`].
+    pub fn try_reserve(&mut self, additional: usize) -> Result<()> {
+        self.owned_mut()
+            .try_reserve(additional)
+            .map_err(|err| Error::Internal {
+                message: format!("failed to reserve {} bytes: {}", additional, err),
+                location: location!(),
+            })
+    }
+
+    /// Reserves capacity for at least `additional` more bytes, see [`Vec::reserve`]
+    pub fn reserve(&mut self, additional: usize) {
+        self.owned_mut().reserve(additional);
+    }
+
+    /// Appends `data` to the end of the buffer, see [`Vec::extend_from_slice`]
+    pub fn extend_from_slice(&mut self, data: &[u8]) {
+        self.owned_mut().extend_from_slice(data);
+    }
+
+    /// Appends the bytes of `other` to the end of the buffer
+    ///
+    /// The bytes are always copied, `other` is dropped afterwards.  See
+    /// [`Self::extend_from_slice`].
+    pub fn append(&mut self, other: Self) {
+        self.extend_from_slice(&other);
+    }
+
+    /// Counts the native-endian values of `T` in the buffer for which `predicate` returns true
+    ///
+    /// Returns an error if the buffer length is not a multiple of the size of `T`.
+    pub fn count_matching<T: ArrowNativeType>(
+        &self,
+        predicate: impl Fn(T) -> bool,
+    ) -> Result<usize> {
+        let values = self.typed_values::<T>()?;
+        Ok(values.iter().filter(|&&value| predicate(value)).count())
+    }
+
+    /// Views the buffer as a slice of `T` without copying
+    ///
+    /// Returns `None` if the buffer is not aligned for `T` or the buffer length is not a
+    /// multiple of the size of `T`.  See [`Self::borrow_to_typed_slice`] for a version that
+    /// copies when needed.
+    pub fn as_typed_ref<T: ArrowNativeType>(&self) -> Option<&[T]> {
+        // SAFETY: ArrowNativeType is only implemented for plain-old-data types where
+        // every bit pattern is a valid value
+        let (prefix, values, suffix) = unsafe { self.as_ref().align_to::<T>() };
+        if prefix.is_empty() && suffix.is_empty() {
+            Some(values)
+        } else {
+            None
+        }
+    }
+
+    /// Concatenates buffers of `T` values into a single buffer that is aligned for `T`
+    ///
+    /// Unlike [`Self::concat`], which returns an owned buffer with no alignment guarantees,
+    /// the combined buffer is allocated with Arrow's allocator (which aligns to at least 64
+    /// bytes) and returned in borrowed mode.  This means later calls to
+    /// [`Self::borrow_to_typed_slice`] or [`Self::as_typed_ref`] are zero-copy.
+    ///
+    /// Returns an error if any of the buffer lengths are not a multiple of the size of `T`.
+    pub fn concat_aligned_typed<T: ArrowNativeType>(buffers: &[Self]) -> Result<Self> {
+        let value_size = std::mem::size_of::<T>();
+        for buffer in buffers {
+            buffer.check_value_width(value_size)?;
+        }
+        let total_len = buffers.iter().map(|b| b.len()).sum();
+        let mut data = MutableBuffer::new(total_len);
+        for buffer in buffers {
+            data.extend_from_slice(buffer.as_ref());
+        }
+        Ok(Self::Borrowed(data.into()))
+    }
+
+    /// Returns a new owned buffer containing the length of this buffer, encoded as an unsigned
+    /// LEB128 varint, followed by the bytes of this buffer
+    ///
+    /// See [`Self::strip_length_prefix`] for the inverse.
+    pub fn with_length_prefix(&self) -> Self {
+        let mut data = Vec::with_capacity(self.len() + MAX_VARINT_BYTES);
+        let mut remaining = self.len() as u64;
+        loop {
+            let byte = (remaining & 0x7F) as u8;
+            remaining >>= 7;
+            if remaining == 0 {
+                data.push(byte);
+                break;
+            }
+            data.push(byte | 0x80);
+        }
+        data.extend_from_slice(self);
+        Self::Owned(data)
+    }
+
+    /// Reads a length prefix written by [`Self::with_length_prefix`]
+    ///
+    /// Returns the decoded length and the remainder of the buffer after the prefix.  The
+    /// remainder is a zero-copy slice if the buffer is borrowed.
+    ///
+    /// Returns an error if the varint is malformed or the remainder is shorter than the
+    /// decoded length.
+    pub fn strip_length_prefix(&self) -> Result<(usize, Self)> {
+        let mut length = 0_u64;
+        let mut prefix_len = 0;
+        loop {
+            let Some(byte) = self.get(prefix_len) else {
+                return Err(Error::invalid_input(
+                    "buffer ended before the end of the length prefix",
+                    location!(),
+                ));
+            };
+            if prefix_len == MAX_VARINT_BYTES - 1 && byte > 1 {
+                return Err(Error::invalid_input(
+                    "length prefix does not fit in 64 bits",
+                    location!(),
+                ));
+            }
+            length |= ((byte & 0x7F) as u64) << (7 * prefix_len);
+            prefix_len += 1;
+            if byte & 0x80 == 0 {
+                break;
+            }
+        }
+        let body_len = self.len() - prefix_len;
+        if length > body_len as u64 {
+            return Err(Error::invalid_input(
+                format!(
+                    "length prefix is {} bytes but only {} bytes follow it",
+                    length, body_len
+                ),
+                location!(),
+            ));
+        }
+        Ok((
+            length as usize,
+            self.slice_with_length(prefix_len, body_len),
+        ))
+    }
+
+    /// Maps each `chunk_size`-byte chunk of the buffer in parallel and reduces the results
+    ///
+    /// The final chunk may be shorter than `chunk_size`.  `reduce` should be associative and
+    /// `identity` should be an identity for `reduce` since the order in which chunk results are
+    /// combined is unspecified.
+    ///
+    /// Panics if `chunk_size` is 0
+    #[cfg(feature = "rayon")]
+    pub fn par_map_reduce<A: Send>(
+        &self,
+        chunk_size: usize,
+        map: impl Fn(&[u8]) -> A + Sync,
+        reduce: impl Fn(A, A) -> A + Sync,
+        identity: A,
+    ) -> A {
+        use rayon::prelude::*;
+
+        assert!(chunk_size > 0, "chunk_size must be greater than 0");
+        match self.par_chunks(chunk_size).map(&map).reduce_with(&reduce) {
+            Some(reduced) => reduce(identity, reduced),
+            None => identity,
+        }
+    }
+
+    /// Swaps the first and second halves of each `record_width`-byte record in place
+    ///
+    /// For example, with 4-byte records `[a, b, c, d]` becomes `[c, d, a, b]`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `record_width` is 0, is odd, or does not evenly divide the buffer length
+    pub fn swap_record_halves(&mut self, record_width: usize) {
+        assert!(
+            record_width > 0 && record_width % 2 == 0,
+            "record_width must be a positive even number but was {}",
+            record_width
+        );
+        assert!(
+            self.len() % record_width == 0,
+            "buffer length {} is not a multiple of the record width {}",
+            self.len(),
+            record_width
+        );
+        let half = record_width / 2;
+        for record in self.owned_mut().chunks_exact_mut(record_width) {
+            let (first, second) = record.split_at_mut(half);
+            first.swap_with_slice(second);
+        }
+    }
+
+    /// Creates a borrowed buffer from a raw pointer and length without copying
+    ///
+    /// The `owner` is kept alive for as long as the buffer (or any slice / clone of it) is
+    /// alive and is dropped once the last reference goes away.  No alignment checks are made.
+    ///
+    /// # Safety
+    ///
+    /// The caller must guarantee that:
+    ///
+    /// * `ptr` is valid for reads of `len` bytes for as long as `owner` is alive
+    /// * the `len` bytes starting at `ptr` are initialized
+    /// * the memory is not mutated (through any pointer) while `owner` is alive
+    /// * dropping `owner` is the only thing that frees the memory (e.g. the memory is not
+    ///   freed by the caller while the buffer is still in use)
+    ///
+    /// Any typed view of the buffer (e.g. [`Self::borrow_to_typed_slice`]) will copy if `ptr`
+    /// is not suitably aligned for the requested type.
+    pub unsafe fn from_raw_parts(
+        ptr: NonNull<u8>,
+        len: usize,
+        owner: Arc<dyn std::any::Any + Send + Sync>,
+    ) -> Self {
+        // The owner is never accessed through the buffer, it only exists to be dropped, and so it
+        // cannot be observed in a broken state after a panic
+        Self::Borrowed(Buffer::from_custom_allocation(
+            ptr,
+            len,
+            Arc::new(std::panic::AssertUnwindSafe(owner)),
+        ))
+    }
+
+    /// Memory-maps `len` bytes of the file at `path`, starting at `offset`, into a mapped
+    /// buffer
+    ///
+    /// The data is not read into memory up front, pages are loaded by the OS as they are
+    /// accessed.  The mapping is kept alive for as long as the buffer (or any slice / clone
+    /// of it) is alive.  The mapping is read-only and the pointer is only page aligned if
+    /// `offset` is a multiple of the page size.
+    ///
+    /// The file must not be modified or truncated while it is mapped, the buffer assumes the
+    /// data is immutable.
+    ///
+    /// Returns an error if the file cannot be opened or mapped, or if the region extends past
+    /// the end of the file.
+    #[cfg(feature = "mmap")]
+    pub fn mmap(path: &std::path::Path, offset: usize, len: usize) -> std::io::Result<Self> {
+        let file = std::fs::File::open(path)?;
+        let file_len = file.metadata()?.len();
+        if offset as u64 + len as u64 > file_len {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "cannot map {} bytes at offset {} of a file with only {} bytes",
+                    len, offset, file_len
+                ),
+            ));
+        }
+        if len == 0 {
+            return Ok(Self::empty());
+        }
+        // SAFETY: the region is within the file and the file is documented as not being
+        // modified while mapped
+        let mmap = unsafe {
+            memmap2::MmapOptions::new()
+                .offset(offset as u64)
+                .len(len)
+                .map(&file)?
+        };
+        let ptr = NonNull::new(mmap.as_ptr() as *mut u8).expect("mmap returned a null pointer");
+        let mmap = Arc::new(mmap);
+        // SAFETY: the mapping is valid for reads of len bytes until the Mmap is dropped and it
+        // is read-only so it cannot be mutated through the buffer
+        let buffer = unsafe { Buffer::from_custom_allocation(ptr, len, mmap.clone()) };
+        Ok(Self::Mapped(MappedBuffer { buffer, mmap }))
+    }
+
+    /// Tells the OS how the buffer is going to be accessed, if it was created by [`Self::mmap`]
+    ///
+    /// This calls `madvise` on the pages covered by the buffer (which may be a slice of the
+    /// original mapping).  For buffers that are not in mapped mode (including mapped buffers
+    /// converted with e.g. [`Self::into_borrowed`]), and on platforms without `madvise`, this
+    /// does nothing and returns `Ok`.
+    ///
+    /// Returns an error if `madvise` fails.
+    #[cfg(feature = "mmap")]
+    pub fn advise(&self, advice: Advice) -> std::io::Result<()> {
+        let Self::Mapped(MappedBuffer { buffer, mmap }) = self else {
+            return Ok(());
+        };
+        if buffer.is_empty() {
+            return Ok(());
+        }
+        let offset = buffer.as_ptr() as usize - mmap.as_ptr() as usize;
+        #[cfg(unix)]
+        match advice {
+            Advice::Sequential => {
+                mmap.advise_range(memmap2::Advice::Sequential, offset, buffer.len())
+            }
+            Advice::Random => mmap.advise_range(memmap2::Advice::Random, offset, buffer.len()),
+            Advice::WillNeed => mmap.advise_range(memmap2::Advice::WillNeed, offset, buffer.len()),
+            // SAFETY: the mapping is a read-only shared file mapping and so dropping the pages is
+            // safe, they are read back from the file on the next access
+            Advice::DontNeed => unsafe {
+                mmap.unchecked_advise_range(
+                    memmap2::UncheckedAdvice::DontNeed,
+                    offset,
+                    buffer.len(),
+                )
+            },
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = (advice, offset);
+            Ok(())
+        }
+    }
+
+    /// Inverts the bits in `[offset_bits, offset_bits + len_bits)` of a bitmap, leaving all
+    /// other bits unchanged
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range extends past the end of the buffer
+    pub fn flip_bits_range(&mut self, offset_bits: usize, len_bits: usize) {
+        let end_bits = offset_bits
+            .checked_add(len_bits)
+            .filter(|end| *end <= self.len() * 8)
+            .unwrap_or_else(|| {
+                panic!(
+                    "flip_bits_range called with bits {}..{}+{} on a buffer of {} bytes",
+                    offset_bits,
+                    offset_bits,
+                    len_bits,
+                    self.len()
+                )
+            });
+        if len_bits == 0 {
+            return;
+        }
+        let data = self.owned_mut();
+        let mut bit = offset_bits;
+        while bit < end_bits {
+            if bit % 8 == 0 && bit + 8 <= end_bits {
+                // Flip whole bytes at once
+                let end_byte = end_bits / 8;
+                data[bit / 8..end_byte]
+                    .iter_mut()
+                    .for_each(|byte| *byte = !*byte);
+                bit = end_byte * 8;
+            } else {
+                data[bit / 8] ^= 1 << (bit % 8);
+                bit += 1;
+            }
+        }
+    }
+
+    /// Dictionary encodes the buffer, interpreted as native-endian values of `T`
+    ///
+    /// Returns `(dictionary, indices)` where `dictionary` holds each distinct value once, in the
+    /// order it was first seen, and `indices` holds one `u32` index into `dictionary` per value.
+    ///
+    /// Returns an error if the buffer length is not a multiple of the size of `T` or if there
+    /// are too many values to index with a `u32`.
+    pub fn build_dictionary<T: ArrowNativeType + Eq + std::hash::Hash>(
+        &self,
+    ) -> Result<(Self, Self)> {
+        let values = self.typed_values::<T>()?;
+        if values.len() > u32::MAX as usize {
+            return Err(Error::invalid_input(
+                format!(
+                    "cannot build a dictionary over {} values, the maximum is {}",
+                    values.len(),
+                    u32::MAX
+                ),
+                location!(),
+            ));
+        }
+        let mut dictionary = Vec::new();
+        let mut positions = HashMap::<T, u32>::new();
+        let indices = values
+            .iter()
+            .map(|value| {
+                *positions.entry(*value).or_insert_with(|| {
+                    dictionary.push(*value);
+                    (dictionary.len() - 1) as u32
+                })
+            })
+            .collect::<Vec<_>>();
+        Ok((Self::from_values(dictionary), Self::from_values(indices)))
+    }
+
+    /// Shortens the buffer to the first `num_values` values of `value_width` bytes each
+    ///
+    /// This has no effect if the buffer is already that short or shorter.  Borrowed and shared
+    /// buffers are sliced (no copy is made) and owned buffers are truncated in place.
+    pub fn truncate_values(&mut self, num_values: usize, value_width: usize) {
+        let num_bytes = num_values.saturating_mul(value_width);
+        if num_bytes >= self.len() {
+            return;
+        }
+        match self {
+            Self::Borrowed(buffer) => *buffer = buffer.slice_with_length(0, num_bytes),
+            Self::Owned(buffer) => buffer.truncate(num_bytes),
+            Self::Shared(_) => *self = self.slice_with_length(0, num_bytes),
+            #[cfg(feature = "mmap")]
+            Self::Mapped(mapped) => mapped.buffer = mapped.buffer.slice_with_length(0, num_bytes),
+        }
+    }
+
+    /// Merges consecutive runs of equal values in run-length encoded data
+    ///
+    /// `values` holds one `value_width`-byte value per run and `run_lengths` holds one `u32`
+    /// length per run.  Returns new owned `(values, run_lengths)` buffers.  A merged run that
+    /// would overflow a `u32` is split into two runs.
+    ///
+    /// Returns an error if `values` is not made up of `value_width`-byte values, if
+    /// `run_lengths` is not made up of `u32` values, or if they disagree on the number of runs.
+    pub fn coalesce_rle_runs(
+        values: &Self,
+        run_lengths: &Self,
+        value_width: usize,
+    ) -> Result<(Self, Self)> {
+        values.check_value_width(value_width)?;
+        let run_lengths = run_lengths.typed_values::<u32>()?;
+        let num_runs = values.len() / value_width;
+        if num_runs != run_lengths.len() {
+            return Err(Error::invalid_input(
+                format!(
+                    "there are {} run values but {} run lengths",
+                    num_runs,
+                    run_lengths.len()
+                ),
+                location!(),
+            ));
+        }
+        let mut merged_values = Vec::<u8>::with_capacity(values.len());
+        let mut merged_lengths = Vec::<u32>::with_capacity(num_runs);
+        for (value, &length) in values.chunks_exact(value_width).zip(run_lengths.iter()) {
+            let last_value = merged_values
+                .len()
+                .checked_sub(value_width)
+                .map(|start| &merged_values[start..]);
+            if last_value == Some(value) {
+                let last_length = merged_lengths.last_mut().unwrap();
+                if let Some(total) = last_length.checked_add(length) {
+                    *last_length = total;
+                    continue;
+                }
+            }
+            merged_values.extend_from_slice(value);
+            merged_lengths.push(length);
+        }
+        Ok((
+            Self::Owned(merged_values),
+            Self::from_values(merged_lengths),
+        ))
+    }
+
+    /// Returns the buffer as an Arrow buffer along with the byte offset and byte length of the
+    /// data within it
+    ///
+    /// This puts the buffer into borrowed mode (see [`Self::borrow_and_clone`]) and is a
+    /// zero-copy operation.  The offset is always 0 since the returned buffer is already sliced
+    /// to the data.  This is convenient when building an `ArrayData` with an explicit offset.
+    pub fn to_arrow_buffer_parts(&mut self) -> (Buffer, usize, usize) {
+        let buffer = self.borrow_and_clone().into_buffer();
+        let len = buffer.len();
+        (buffer, 0, len)
+    }
+
+    /// Reverses the order of the first `len_bits` bits of a bitmap, returning a new owned
+    /// bitmap
+    ///
+    /// Bit `i` of the output is bit `len_bits - 1 - i` of the input, any padding bits in the
+    /// final byte are unset.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len_bits` is greater than the number of bits in the buffer
+    pub fn reverse_bitmap(&self, len_bits: usize) -> Self {
+        assert!(
+            len_bits <= self.len() * 8,
+            "reverse_bitmap called with {} bits on a buffer of {} bytes",
+            len_bits,
+            self.len()
+        );
+        let mut reversed = vec![0; len_bits.div_ceil(8)];
+        for bit in BitIndexIterator::new(self, 0, len_bits) {
+            bit_util::set_bit(&mut reversed, len_bits - 1 - bit);
+        }
+        Self::Owned(reversed)
+    }
+
+    /// Appends the uppercase hex encoding of the buffer to `out`
+    ///
+    /// This produces the same text as [`Self::as_hex`] but reuses the allocation of `out`
+    /// which is useful in hot paths (e.g. logging) that format many buffers.
+    pub fn write_hex_into(&self, out: &mut String) {
+        const HEX_DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+        out.reserve(self.len() * 2);
+        for byte in self.iter() {
+            out.push(HEX_DIGITS[(byte >> 4) as usize] as char);
+            out.push(HEX_DIGITS[(byte & 0x0F) as usize] as char);
+        }
+    }
+
+    /// Splits a buffer of interleaved records into one buffer per field
+    ///
+    /// Each record consists of fields of the given `field_widths` (in bytes) laid out one after
+    /// another.  This is the inverse of [`Self::zip_into_one`] and returns one owned buffer per
+    /// field containing that field's value from every record.
+    ///
+    /// Returns an error if the buffer length is not `sum(field_widths) * num_records`.
+    pub fn split_soa(&self, field_widths: &[usize], num_records: u64) -> Result<Vec<Self>> {
+        let record_width = field_widths.iter().sum::<usize>();
+        let expected_len = usize::try_from(num_records)
+            .ok()
+            .and_then(|num_records| record_width.checked_mul(num_records));
+        if expected_len != Some(self.len()) {
+            return Err(Error::invalid_input(
+                format!(
+                    "a buffer of {} bytes cannot hold {} records of {} bytes each",
+                    self.len(),
+                    num_records,
+                    record_width
+                ),
+                location!(),
+            ));
+        }
+        let mut fields = field_widths
+            .iter()
+            .map(|width| Vec::with_capacity(width * num_records as usize))
+            .collect::<Vec<_>>();
+        if record_width > 0 {
+            for record in self.chunks_exact(record_width) {
+                let mut offset = 0;
+                for (field, width) in fields.iter_mut().zip(field_widths) {
+                    field.extend_from_slice(&record[offset..offset + width]);
+                    offset += width;
+                }
+            }
+        }
+        Ok(fields.into_iter().map(Self::Owned).collect())
+    }
+
+    /// Returns the number of leading bytes shared by every record
+    ///
+    /// Returns 0 if `records` is empty or if any two records differ at the first byte.  If the
+    /// records have different lengths the prefix is limited to the shortest record.
+    pub fn count_leading_equal_bytes(records: &[Self]) -> usize {
+        let Some((first, rest)) = records.split_first() else {
+            return 0;
+        };
+        rest.iter().fold(first.len(), |prefix_len, record| {
+            first[..prefix_len]
+                .iter()
+                .zip(record.iter())
+                .take_while(|(a, b)| a == b)
+                .count()
+        })
+    }
+
+    /// Returns an iterator over the maximal runs of equal `value_width`-byte values
+    ///
+    /// Each item is `(value, run_length)`.  The runs are computed lazily so this can be used to
+    /// consume run-length encoded data without materializing the run values and lengths.  Any
+    /// trailing bytes that do not form a full value are ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value_width` is 0
+    pub fn runs(&self, value_width: usize) -> impl Iterator<Item = (&[u8], usize)> + '_ {
+        self.chunks_exact(value_width)
+            .dedup_with_count()
+            .map(|(run_length, value)| (value, run_length))
+    }
+
+    /// Builds a rank index for the first `len_bits` bits of a bitmap
+    ///
+    /// Entry `i` of the index is the number of set bits before bit `i * block_bits`, for each
+    /// block boundary in `0..=len_bits`.  See [`Self::rank`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `block_bits` is 0 or `len_bits` is greater than the number of bits in the
+    /// buffer
+    pub fn build_rank_index(&self, len_bits: usize, block_bits: usize) -> Vec<u32> {
+        assert!(block_bits > 0, "block_bits must be greater than 0");
+        assert!(
+            len_bits <= self.len() * 8,
+            "build_rank_index called with {} bits on a buffer of {} bytes",
+            len_bits,
+            self.len()
+        );
+        let mut index = Vec::with_capacity(len_bits / block_bits + 1);
+        let mut total = 0_u32;
+        index.push(total);
+        for block_start in (0..len_bits / block_bits).map(|block| block * block_bits) {
+            total += self.count_ones_range(block_start, block_bits) as u32;
+            index.push(total);
+        }
+        index
+    }
+
+    /// Returns the number of set bits before bit `pos`
+    ///
+    /// `index` must have been built by [`Self::build_rank_index`] on this buffer with the same
+    /// `block_bits`.  Only the bits between the start of `pos`'s block and `pos` are scanned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pos` is beyond the range covered by `index`
+    pub fn rank(&self, index: &[u32], block_bits: usize, pos: usize) -> usize {
+        let block = pos / block_bits;
+        assert!(
+            block < index.len(),
+            "rank called with position {} but the index only covers {} blocks of {} bits",
+            pos,
+            index.len(),
+            block_bits
+        );
+        let block_start = block * block_bits;
+        index[block] as usize + self.count_ones_range(block_start, pos - block_start) as usize
+    }
+
+    /// Returns the position of the `n`th (0-based) set bit within the first `len_bits` bits of
+    /// a bitmap, or `None` if there are `n` or fewer set bits
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len_bits` is greater than the number of bits in the buffer
+    pub fn select(&self, n: usize, len_bits: usize) -> Option<usize> {
+        assert!(
+            len_bits <= self.len() * 8,
+            "select called with {} bits on a buffer of {} bytes",
+            len_bits,
+            self.len()
+        );
+        let chunks = BitChunks::new(self, 0, len_bits);
+        let mut remaining = n;
+        for (chunk_idx, mut chunk) in chunks
+            .iter()
+            .chain(std::iter::once(chunks.remainder_bits()))
+            .enumerate()
+        {
+            let count = chunk.count_ones() as usize;
+            if remaining < count {
+                // Clear the lower set bits until the requested bit is the lowest set bit
+                for _ in 0..remaining {
+                    chunk &= chunk - 1;
+                }
+                return Some(chunk_idx * 64 + chunk.trailing_zeros() as usize);
+            }
+            remaining -= count;
+        }
+        None
+    }
+
+    /// Concatenates the values and validity bitmaps of several chunks of a nullable column
+    ///
+    /// Each chunk is `(values, validity, num_values)` where `values` holds `num_values` values
+    /// of `value_width` bytes and `validity` is an optional bitmap with one bit per value.  A
+    /// validity of `None` means every value in the chunk is valid.
+    ///
+    /// Returns the concatenated values and the concatenated bitmap.  The bitmap is `None` if
+    /// every chunk was entirely valid (see [`Self::concat_optional_bitmaps`]).
+    ///
+    /// Returns an error if any chunk's values or validity are too short for `num_values`.
+    pub fn concat_nullable(
+        chunks: &[(Self, Option<Self>, usize)],
+        value_width: usize,
+    ) -> Result<(Self, Option<Self>)> {
+        for (chunk_idx, (values, validity, num_values)) in chunks.iter().enumerate() {
+            if values.len() != num_values * value_width {
+                return Err(Error::invalid_input(
+                    format!(
+                        "chunk {} has {} bytes of values but should have {} values of {} bytes",
+                        chunk_idx,
+                        values.len(),
+                        num_values,
+                        value_width
+                    ),
+                    location!(),
+                ));
+            }
+            if let Some(validity) = validity {
+                if validity.len() * 8 < *num_values {
+                    return Err(Error::invalid_input(
+                        format!(
+                            "chunk {} has a validity bitmap of {} bytes which cannot hold {} values",
+                            chunk_idx,
+                            validity.len(),
+                            num_values
+                        ),
+                        location!(),
+                    ));
+                }
+            }
+        }
+        let total_len = chunks.iter().map(|(values, _, _)| values.len()).sum();
+        let mut values = Vec::with_capacity(total_len);
+        for (chunk_values, _, _) in chunks {
+            values.extend_from_slice(chunk_values);
+        }
+        let validity_parts = chunks
+            .iter()
+            .map(|(_, validity, num_values)| (validity.as_ref(), *num_values))
+            .collect::<Vec<_>>();
+        let validity = Self::concat_optional_bitmaps(&validity_parts).map(|(bitmap, _)| bitmap);
+        Ok((Self::Owned(values), validity))
+    }
+
+    /// Computes summary statistics over the buffer, interpreted as native-endian values of `T`,
+    /// in a single pass
+    ///
+    /// These are intended to help choose an encoding for a column (see [`ColumnStats`]).  The
+    /// number of distinct values is an estimate.  It uses a hasher with fixed keys so the same
+    /// values give the same estimate on every run.
+    ///
+    /// Returns an error if the buffer length is not a multiple of the size of `T`.
+    pub fn column_stats<T: ArrowNativeType + Ord + Eq + std::hash::Hash>(
+        &self,
+    ) -> Result<ColumnStats<T>> {
+        const PRECISION: u8 = 12;
+        type FixedState = std::hash::BuildHasherDefault<std::collections::hash_map::DefaultHasher>;
+        let values = self.typed_values::<T>()?;
+        let mut hll: HyperLogLogPlus<T, FixedState> =
+            HyperLogLogPlus::new(PRECISION, FixedState::default()).unwrap();
+        let mut num_runs = 0;
+        let mut min = None;
+        let mut max = None;
+        let mut prev = None;
+        for &value in values.iter() {
+            if prev != Some(value) {
+                num_runs += 1;
+                prev = Some(value);
+            }
+            min = Some(min.map_or(value, |min: T| min.min(value)));
+            max = Some(max.map_or(value, |max: T| max.max(value)));
+            hll.insert(&value);
+        }
+        Ok(ColumnStats {
+            num_values: values.len(),
+            num_runs,
+            num_distinct: hll.count().round() as u64,
+            min,
+            max,
+            is_constant: num_runs == 1,
+        })
+    }
+
+    /// Returns the XOR of every byte in the buffer
+    ///
+    /// This is an extremely cheap but weak integrity check.  It will detect corruption that is
+    /// confined to a single byte but many multi-byte errors (e.g. two bytes with the same bit
+    /// flipped, or swapped bytes) cancel out and go unnoticed.  Use a real checksum when the
+    /// integrity of the data matters.
+    pub fn xor_parity(&self) -> u8 {
+        let chunks = self.chunks_exact(8);
+        let remainder = chunks
+            .remainder()
+            .iter()
+            .fold(0_u8, |parity, byte| parity ^ byte);
+        let folded = chunks
+            .map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()))
+            .fold(0_u64, |parity, word| parity ^ word);
+        folded
+            .to_le_bytes()
+            .iter()
+            .fold(remainder, |parity, byte| parity ^ byte)
+    }
+
+    /// Builds an Arrow dictionary array from a buffer of keys and a buffer of dictionary values
+    ///
+    /// `indices` holds native-endian `K::Native` keys and `dictionary` holds native-endian
+    /// `V::Native` values (e.g. the output of [`Self::build_dictionary`] after converting the
+    /// indices to the key type).  Both buffers are wrapped without copying if they are
+    /// suitably aligned.  The resulting array has no nulls.
+    ///
+    /// Returns an error if either buffer is not made up of whole values or if any key is out
+    /// of range for the dictionary.
+    pub fn into_dictionary_array<
+        K: arrow_array::types::ArrowDictionaryKeyType,
+        V: arrow_array::ArrowPrimitiveType,
+    >(
+        mut indices: Self,
+        mut dictionary: Self,
+    ) -> Result<arrow_array::DictionaryArray<K>> {
+        indices.check_value_width(std::mem::size_of::<K::Native>())?;
+        dictionary.check_value_width(std::mem::size_of::<V::Native>())?;
+        let keys = arrow_array::PrimitiveArray::<K>::new(
+            indices.borrow_to_typed_slice::<K::Native>(),
+            None,
+        );
+        let values = arrow_array::PrimitiveArray::<V>::new(
+            dictionary.borrow_to_typed_slice::<V::Native>(),
+            None,
+        );
+        Ok(arrow_array::DictionaryArray::try_new(
+            keys,
+            Arc::new(values),
+        )?)
+    }
+
+    /// Creates a bitmap from the values of an Arrow boolean array
+    ///
+    /// Returns the bitmap, starting at bit 0, and the number of bits.  The validity of the array
+    /// is ignored.  This only copies if the array has a non-zero offset.
+    pub fn from_boolean_array(array: &arrow_array::BooleanArray) -> (Self, usize) {
+        let values = array.values();
+        (Self::Borrowed(values.sliced()), values.len())
+    }
+
+    /// Splits the buffer into two parts at the value boundary nearest to `ratio` of the way
+    /// through the buffer
+    ///
+    /// The first part holds `round(ratio * num_values)` values of `value_width` bytes and the
+    /// second part holds the rest (including any trailing bytes that do not form a full value).
+    /// Borrowed buffers are split without copying, owned buffers are copied.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ratio` is not in `[0.0, 1.0]` or if `value_width` is 0
+    pub fn split_at_ratio(&self, ratio: f64, value_width: usize) -> (Self, Self) {
+        assert!(
+            (0.0..=1.0).contains(&ratio),
+            "split_at_ratio requires a ratio between 0 and 1 but received {}",
+            ratio
+        );
+        assert!(value_width > 0, "value_width must be greater than 0");
+        let num_values = self.len() / value_width;
+        let split_values = ((ratio * num_values as f64).round() as usize).min(num_values);
+        let split_bytes = split_values * value_width;
+        (
+            self.slice_with_length(0, split_bytes),
+            self.slice_with_length(split_bytes, self.len() - split_bytes),
+        )
+    }
+
+    /// Copies the buffer into a new owned allocation if it is a small view into a much larger
+    /// allocation
+    ///
+    /// A borrowed buffer keeps its entire backing allocation alive, even if it is only a small
+    /// slice of it.  This should be called on buffers that are retained for a long time so the
+    /// rest of the allocation can be freed.  This is a no-op for owned and shared buffers and
+    /// for borrowed buffers that use at least a quarter of their allocation.
+    ///
+    /// Only allocations whose size is known are compacted, i.e. those created from a `Vec`, an
+    /// `Arc<[u8]>`, or by arrow.  A buffer created with [`Self::from_bytes`] reports the length
+    /// of the `Bytes` it wraps as its capacity, even if that `Bytes` is a slice of a larger
+    /// allocation, so it is never compacted.
+    pub fn compact(&mut self) {
+        if let Self::Borrowed(buffer) = self {
+            if buffer.len().saturating_mul(COMPACT_RATIO) < buffer.capacity() {
+                *self = Self::Owned(buffer.to_vec());
+            }
+        }
+    }
+
+    /// Applies a bitwise operation to each pair of bytes in two equal-length buffers
+    ///
+    /// The bytes are processed a word at a time with `word_op` and any remaining bytes are
+    /// processed with `byte_op`.  Both must perform the same operation.
+    fn bitwise_binary_op(
+        &self,
+        other: &Self,
+        op_name: &str,
+        word_op: impl Fn(usize, usize) -> usize,
+        byte_op: impl Fn(u8, u8) -> u8,
+    ) -> Result<Self> {
+        if self.len() != other.len() {
+            return Err(Error::invalid_input(
+                format!(
+                    "{} requires buffers of the same length but received buffers of {} and {} bytes",
+                    op_name,
+                    self.len(),
+                    other.len()
+                ),
+                location!(),
+            ));
+        }
+        const WORD_SIZE: usize = std::mem::size_of::<usize>();
+        let mut result = Vec::with_capacity(self.len());
+        let left = self.chunks_exact(WORD_SIZE);
+        let right = other.chunks_exact(WORD_SIZE);
+        let (left_tail, right_tail) = (left.remainder(), right.remainder());
+        for (l, r) in left.zip(right) {
+            let l = usize::from_ne_bytes(l.try_into().unwrap());
+            let r = usize::from_ne_bytes(r.try_into().unwrap());
+            result.extend_from_slice(&word_op(l, r).to_ne_bytes());
+        }
+        result.extend(
+            left_tail
+                .iter()
+                .zip(right_tail)
+                .map(|(l, r)| byte_op(*l, *r)),
+        );
+        Ok(Self::Owned(result))
+    }
+
+    /// Computes the bitwise AND of two buffers of the same length, returning a new owned buffer
+    ///
+    /// Returns an error if the buffers have different lengths
+    pub fn bit_and(&self, other: &Self) -> Result<Self> {
+        self.bitwise_binary_op(other, "bit_and", |l, r| l & r, |l, r| l & r)
+    }
+
+    /// Computes the bitwise OR of two buffers of the same length, returning a new owned buffer
+    ///
+    /// Returns an error if the buffers have different lengths
+    pub fn bit_or(&self, other: &Self) -> Result<Self> {
+        self.bitwise_binary_op(other, "bit_or", |l, r| l | r, |l, r| l | r)
+    }
+
+    /// Computes the bitwise XOR of two buffers of the same length, returning a new owned buffer
+    ///
+    /// Returns an error if the buffers have different lengths
+    pub fn bit_xor(&self, other: &Self) -> Result<Self> {
+        self.bitwise_binary_op(other, "bit_xor", |l, r| l ^ r, |l, r| l ^ r)
+    }
+
+    /// Verifies the CRC32C checksum of a zstd compressed buffer and then decompresses it
+    ///
+    /// `expected_crc` is the checksum of the compressed bytes and `decompressed_len` is the
+    /// exact size of the data once decompressed.  A matching checksum does not make
+    /// `decompressed_len` trustworthy so it is validated as in [`Self::decompress_zstd`].
+    /// Returns a new owned buffer.
+    ///
+    /// Returns an error if the checksum does not match (the data was corrupted in storage or
+    /// in transit) or if the checksum matches but the data cannot be decompressed into exactly
+    /// `decompressed_len` bytes (the data was corrupt before the checksum was computed or
+    /// `decompressed_len` is wrong).  The error messages distinguish the two cases.
+    #[cfg(all(feature = "checksum", feature = "zstd"))]
+    pub fn verify_and_decompress_zstd(
+        &self,
+        expected_crc: u32,
+        decompressed_len: usize,
+    ) -> Result<Self> {
+        let actual_crc = crc32c::crc32c(self);
+        if actual_crc != expected_crc {
+            return Err(Error::invalid_input(
+                format!(
+                    "checksum mismatch in compressed block: expected crc32c {:#010x} but computed {:#010x}",
+                    expected_crc, actual_crc
+                ),
+                location!(),
+            ));
+        }
+        let decompressed =
+            Self::decompress_zstd_slice(self, decompressed_len).map_err(|err| match err {
+                Error::InvalidInput { source, .. } => {
+                    Error::invalid_input(format!("checksum verified but {}", source), location!())
+                }
+                err => err,
+            })?;
+        Ok(Self::Owned(decompressed))
+    }
+
+    /// Compresses the buffer into a self-describing block
+    ///
+    /// The block is laid out as `[magic:4][codec:1][uncompressed_len:u64][crc32c:u32][payload]`
+    /// where integers are little-endian and the checksum is computed over the uncompressed
+    /// data.  Use [`Self::unpack_block`] to read the block back.  Zstd compression uses the
+    /// default level.
+    ///
+    /// Returns an error if compression fails.
+    #[cfg(feature = "checksum")]
+    pub fn pack_block(&self, codec: Codec) -> Result<Self> {
+        let payload = match codec {
+            Codec::None => Cow::Borrowed(self.as_ref()),
+            #[cfg(feature = "zstd")]
+            Codec::Zstd => Cow::Owned(zstd::bulk::compress(self, 0)?),
+            #[cfg(feature = "lz4")]
+            Codec::Lz4 => Cow::Owned(lz4::block::compress(self, None, false)?),
+        };
+        let mut block = Vec::with_capacity(PACKED_BLOCK_HEADER_BYTES + payload.len());
+        block.extend_from_slice(PACKED_BLOCK_MAGIC);
+        block.push(codec.id());
+        block.extend_from_slice(&(self.len() as u64).to_le_bytes());
+        block.extend_from_slice(&crc32c::crc32c(self).to_le_bytes());
+        block.extend_from_slice(&payload);
+        Ok(Self::Owned(block))
+    }
+
+    /// Validates and decompresses a block written by [`Self::pack_block`], returning a new
+    /// owned buffer
+    ///
+    /// The header is untrusted, the recorded uncompressed length is validated against the
+    /// payload (see [`Self::decompress_zstd`] and [`Self::decompress_lz4`]) before the output is
+    /// allocated so a corrupt length is reported as an error.
+    ///
+    /// Returns an error if the buffer is too short to hold a header, does not start with the
+    /// expected magic bytes, names an unknown codec (or one whose feature is not enabled),
+    /// cannot be decompressed into exactly the recorded number of bytes (e.g. the payload was
+    /// truncated or the length is corrupt), or if the checksum of the decompressed data does
+    /// not match.  The error messages distinguish these cases.
+    #[cfg(feature = "checksum")]
+    pub fn unpack_block(&self) -> Result<Self> {
+        if self.len() < PACKED_BLOCK_HEADER_BYTES {
+            return Err(Error::invalid_input(
+                format!(
+                    "truncated block: a packed block header is {} bytes but only {} bytes are available",
+                    PACKED_BLOCK_HEADER_BYTES,
+                    self.len()
+                ),
+                location!(),
+            ));
+        }
+        let (header, payload) = self.as_ref().split_at(PACKED_BLOCK_HEADER_BYTES);
+        if &header[0..4] != PACKED_BLOCK_MAGIC {
+            return Err(Error::invalid_input(
+                format!(
+                    "not a packed block: expected magic {:?} but found {:?}",
+                    PACKED_BLOCK_MAGIC,
+                    &header[0..4]
+                ),
+                location!(),
+            ));
+        }
+        let codec = Codec::from_id(header[4])?;
+        let uncompressed_len = u64::from_le_bytes(header[5..13].try_into().unwrap());
+        let expected_crc = u32::from_le_bytes(header[13..17].try_into().unwrap());
+
+        #[cfg(any(feature = "zstd", feature = "lz4"))]
+        let addressable_len = || {
+            usize::try_from(uncompressed_len).map_err(|_| {
+                Error::invalid_input(
+                    format!(
+                        "packed block header records {} uncompressed bytes which cannot be addressed on this platform",
+                        uncompressed_len
+                    ),
+                    location!(),
+                )
+            })
+        };
+        let decompressed = match codec {
+            Codec::None => {
+                if payload.len() as u64 != uncompressed_len {
+                    return Err(Error::invalid_input(
+                        format!(
+                            "packed block payload is {} bytes but the header records {} uncompressed bytes",
+                            payload.len(),
+                            uncompressed_len
+                        ),
+                        location!(),
+                    ));
+                }
+                payload.to_vec()
+            }
+            #[cfg(feature = "zstd")]
+            Codec::Zstd => Self::decompress_zstd_slice(payload, addressable_len()?)?,
+            #[cfg(feature = "lz4")]
+            Codec::Lz4 => Self::decompress_lz4_slice(payload, addressable_len()?)?,
+        };
+        let actual_crc = crc32c::crc32c(&decompressed);
+        if actual_crc != expected_crc {
+            return Err(Error::invalid_input(
+                format!(
+                    "checksum mismatch in packed block: expected crc32c {:#010x} but computed {:#010x}",
+                    expected_crc, actual_crc
+                ),
+                location!(),
+            ));
+        }
+        Ok(Self::Owned(decompressed))
+    }
+
+    /// Computes the bitwise NOT of the buffer, returning a new owned buffer
+    ///
+    /// Every bit is inverted, including any padding bits past the logical end of a bitmap.  Use
+    /// [`Self::bit_not_with_len`] to keep the padding bits unset.
+    pub fn bit_not(&self) -> Self {
+        const WORD_SIZE: usize = std::mem::size_of::<usize>();
+        let mut result = Vec::with_capacity(self.len());
+        let words = self.chunks_exact(WORD_SIZE);
+        let tail = words.remainder();
+        for word in words {
+            let word = usize::from_ne_bytes(word.try_into().unwrap());
+            result.extend_from_slice(&(!word).to_ne_bytes());
+        }
+        result.extend(tail.iter().map(|byte| !byte));
+        Self::Owned(result)
+    }
+
+    /// Computes the bitwise NOT of the first `num_bits` bits, returning a new owned buffer of
+    /// the same length
+    ///
+    /// Any bits at or after `num_bits` are unset in the result.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num_bits` is greater than the number of bits in the buffer
+    pub fn bit_not_with_len(&self, num_bits: usize) -> Self {
+        assert!(
+            num_bits <= self.len() * 8,
+            "bit_not_with_len called with {} bits on a buffer of {} bytes",
+            num_bits,
+            self.len()
+        );
+        let mut result = self.bit_not();
+        let data = result.owned_mut();
+        let num_bytes = num_bits.div_ceil(8);
+        data[num_bytes..].fill(0);
+        if num_bits % 8 != 0 {
+            data[num_bytes - 1] &= (1 << (num_bits % 8)) - 1;
+        }
+        result
+    }
+
+    /// Converts a buffer of offsets into a buffer of lengths, returning a new owned buffer
+    ///
+    /// The buffer is interpreted as native-endian `T` offsets and length `i` of the result is
+    /// `offsets[i + 1] - offsets[i]`, so there is one fewer length than there are offsets.
+    /// See [`Self::lengths_to_offsets`] for the inverse.
+    ///
+    /// Returns an error if the buffer length is not a multiple of the size of `T` or if the
+    /// offsets are not monotonically non-decreasing.
+    pub fn offsets_to_lengths<T: ArrowNativeType + std::ops::Sub<Output = T> + PartialOrd>(
+        &self,
+    ) -> Result<Self> {
+        let offsets = self.typed_values::<T>()?;
+        if let Some(position) = offsets.windows(2).position(|pair| pair[1] < pair[0]) {
+            return Err(Error::invalid_input(
+                format!(
+                    "offsets must be non-decreasing but offset {} ({:?}) is greater than offset {} ({:?})",
+                    position,
+                    offsets[position],
+                    position + 1,
+                    offsets[position + 1]
+                ),
+                location!(),
+            ));
+        }
+        Ok(Self::from_values(
+            offsets.windows(2).map(|pair| pair[1] - pair[0]),
+        ))
+    }
+
+    /// Converts a buffer of lengths into a buffer of offsets, returning a new owned buffer
+    ///
+    /// The buffer is interpreted as native-endian `T` lengths.  The result starts with a zero
+    /// offset followed by the running sum of the lengths, so there is one more offset than
+    /// there are lengths.  This is the inverse of [`Self::offsets_to_lengths`] (for offsets that
+    /// start at zero).
+    ///
+    /// Returns an error if the buffer length is not a multiple of the size of `T` or if the
+    /// sum of the lengths overflows `T`.
+    pub fn lengths_to_offsets<T: ArrowNativeType + CheckedAdd>(&self) -> Result<Self> {
+        let lengths = self.typed_values::<T>()?;
+        let mut offsets = Vec::with_capacity(lengths.len() + 1);
+        let mut offset = T::usize_as(0);
+        offsets.push(offset);
+        for length in lengths.iter() {
+            offset = offset.checked_add(length).ok_or_else(|| {
+                Error::invalid_input(
+                    format!(
+                        "the sum of {} lengths overflows the offset type",
+                        lengths.len()
+                    ),
+                    location!(),
+                )
+            })?;
+            offsets.push(offset);
+        }
+        Ok(Self::from_values(offsets))
+    }
+
+    /// Returns the number of set bits in the buffer
+    ///
+    /// The bytes are counted a `u64` word at a time, with the unaligned bytes at either end
+    /// counted individually.
+    pub fn count_ones(&self) -> u64 {
+        // SAFETY: u64 is plain-old-data and every bit pattern is a valid value
+        let (prefix, words, suffix) = unsafe { self.as_ref().align_to::<u64>() };
+        let count_bytes = |bytes: &[u8]| {
+            bytes
+                .iter()
+                .map(|byte| byte.count_ones() as u64)
+                .sum::<u64>()
+        };
+        count_bytes(prefix)
+            + words
+                .iter()
+                .map(|word| word.count_ones() as u64)
+                .sum::<u64>()
+            + count_bytes(suffix)
+    }
+
+    /// Returns the number of set bits in `[bit_offset, bit_offset + num_bits)` of a bitmap
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range extends past the end of the buffer
+    pub fn count_ones_range(&self, bit_offset: usize, num_bits: usize) -> u64 {
+        assert!(
+            bit_offset.saturating_add(num_bits) <= self.len() * 8,
+            "count_ones_range called with {} bits at offset {} on a buffer of {} bytes",
+            num_bits,
+            bit_offset,
+            self.len()
+        );
+        UnalignedBitChunk::new(self, bit_offset, num_bits).count_ones() as u64
+    }
+
+    /// Views the buffer as native-endian values of `T` without copying
+    ///
+    /// Unlike [`Self::borrow_to_typed_slice`] this never copies.  Instead it returns an error if
+    /// the buffer is not aligned for `T`, which is useful for callers that rely on the view
+    /// being zero-copy.  Like [`Self::borrow_and_clone`] this puts the buffer into borrowed mode.
+    ///
+    /// Returns an error if the buffer length is not a multiple of the size of `T` or if the
+    /// buffer is not aligned for `T`.
+    pub fn reinterpret_width<T: ArrowNativeType>(&mut self) -> Result<ScalarBuffer<T>> {
+        self.check_value_width(std::mem::size_of::<T>())?;
+        let align = std::mem::align_of::<T>();
+        if self.as_ptr().align_offset(align) != 0 {
+            return Err(Error::invalid_input(
+                format!(
+                    "cannot reinterpret a buffer at address {:p} as values that require {}-byte alignment without copying",
+                    self.as_ptr(),
+                    align
+                ),
+                location!(),
+            ));
+        }
+        Ok(ScalarBuffer::<T>::from(
+            self.borrow_and_clone().into_buffer(),
+        ))
+    }
+
+    /// Counts the occurrences of each distinct value in the buffer, interpreted as
+    /// native-endian values of `T`
+    ///
+    /// This is intended for low cardinality columns since the result has one entry per
+    /// distinct value.
+    ///
+    /// Returns an error if the buffer length is not a multiple of the size of `T`.
+    pub fn value_histogram<T: ArrowNativeType + Eq + std::hash::Hash>(
+        &self,
+    ) -> Result<HashMap<T, usize>> {
+        let values = self.typed_values::<T>()?;
+        let mut histogram = HashMap::new();
+        for value in values.iter() {
+            *histogram.entry(*value).or_insert(0) += 1;
+        }
+        Ok(histogram)
+    }
+
+    /// Sets every `value_width`-byte value whose validity bit is unset to zero
+    ///
+    /// `validity` is a bitmap with one bit per value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value_width` is 0, if the buffer is not made up of whole values, or if the
+    /// validity bitmap has fewer bits than there are values
+    pub fn zero_null_values(&mut self, validity: &Self, value_width: usize) {
+        assert!(
+            value_width > 0 && self.len() % value_width == 0,
+            "a buffer of {} bytes cannot be split into values of {} bytes",
+            self.len(),
+            value_width
+        );
+        let num_values = self.len() / value_width;
+        assert!(
+            validity.len() * 8 >= num_values,
+            "a validity bitmap of {} bytes is too short for {} values",
+            validity.len(),
+            num_values
+        );
+        let data = self.owned_mut();
+        for (index, value) in data.chunks_exact_mut(value_width).enumerate() {
+            if !bit_util::get_bit(validity, index) {
+                value.fill(0);
+            }
+        }
+    }
+
+    /// Creates a borrowed buffer holding a copy of `data` whose start is aligned to `align`
+    ///
+    /// Reinterpreting the result (e.g. with [`Self::borrow_to_typed_slice`]) as any type with an
+    /// alignment of at most `align` will never need to copy.  This is also true when `data` is
+    /// empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `align` is not a power of two
+    pub fn with_alignment(data: &[u8], align: usize) -> Self {
+        let mut allocation = AlignedAllocation::zeroed(data.len(), align);
+        allocation.as_mut_slice().copy_from_slice(data);
+        Self::Borrowed(allocation.into_buffer())
+    }
+
+    /// Returns true if the start of the buffer is aligned to `align` bytes
+    ///
+    /// Note that this checks the actual address of the data.  An empty buffer (e.g. one created
+    /// by [`Self::empty`]) may not be aligned.
+    ///
+    /// Panics if `align` is not a power of two.
+    pub fn is_aligned_to(&self, align: usize) -> bool {
+        assert!(
+            align.is_power_of_two(),
+            "alignment must be a power of two but was {}",
+            align
+        );
+        self.as_ptr() as usize % align == 0
+    }
+
+    /// Concatenates multiple buffers into a single buffer and computes the CRC32C checksum of
+    /// each input buffer
+    ///
+    /// The checksums are returned in the same order as the input buffers.  Each checksum is
+    /// computed as its buffer is copied so the data is only read once.
+    #[cfg(feature = "checksum")]
+    pub fn concat_with_component_crcs(buffers: Vec<Self>) -> (Self, Vec<u32>) {
+        let total_len = buffers.iter().map(|b| b.len()).sum();
+        let mut data = Vec::with_capacity(total_len);
+        let crcs = buffers
+            .iter()
+            .map(|buffer| {
+                data.extend_from_slice(buffer);
+                crc32c::crc32c(buffer)
+            })
+            .collect();
+        (Self::Owned(data), crcs)
+    }
+
+    /// Binary searches a sorted buffer, interpreted as native-endian values of `T`, for
+    /// `target`
+    ///
+    /// The inner result has the same meaning as [`slice::binary_search`]: `Ok(index)` is the
+    /// index of a matching value and `Err(index)` is the index where `target` could be inserted
+    /// while keeping the values sorted.  The buffer must be sorted in ascending order, otherwise
+    /// the result is unspecified.
+    ///
+    /// Returns an error if the buffer length is not a multiple of the size of `T`.
+    pub fn binary_search_value<T: ArrowNativeType + Ord>(
+        &self,
+        target: T,
+    ) -> Result<std::result::Result<usize, usize>> {
+        let values = self.typed_values::<T>()?;
+        Ok(values.binary_search(&target))
+    }
+
+    /// Copies the bytes in `src` to the position starting at `dest`, the ranges may overlap
+    ///
+    /// This has the same semantics as [`slice::copy_within`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `src` is out of bounds, if `src.start > src.end`, or if the destination range
+    /// extends past the end of the buffer
+    pub fn copy_within(&mut self, src: Range<usize>, dest: usize) {
+        assert!(
+            src.start <= src.end
+                && src.end <= self.len()
+                && dest.saturating_add(src.len()) <= self.len(),
+            "copy_within from {:?} to {} is out of bounds for a buffer of {} bytes",
+            src,
+            dest,
+            self.len()
+        );
+        self.owned_mut().copy_within(src, dest);
+    }
+
+    /// Concatenates `other` onto the end of this buffer, consuming both
+    ///
+    /// This allows concatenation to be chained (e.g. `a.then(b).then(c)`).  If this buffer is
+    /// owned (or is the sole reference to a borrowed allocation) then `other` is appended in
+    /// place, reusing any spare capacity.  If either buffer is empty the other is returned as
+    /// is.  The result is the same as calling [`Self::concat_into_one`] on both buffers.
+    pub fn then(mut self, other: Self) -> Self {
+        if self.is_empty() {
+            return other;
+        }
+        if other.is_empty() {
+            return self;
+        }
+        self.owned_mut().extend_from_slice(&other);
+        self
+    }
+
+    /// Returns a random sample of up to `num_samples` values of `value_width` bytes each
+    ///
+    /// The sample is chosen with reservoir sampling using an RNG seeded with `seed` so the same
+    /// seed always produces the same sample.  If there are no more than `num_samples` values
+    /// then every value is returned (in order).  Returns a new owned buffer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value_width` is 0 or the buffer is not made up of whole values
+    pub fn reservoir_sample(&self, num_samples: usize, value_width: usize, seed: u64) -> Self {
+        assert!(
+            value_width > 0 && self.len() % value_width == 0,
+            "a buffer of {} bytes cannot be split into values of {} bytes",
+            self.len(),
+            value_width
+        );
+        let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+        let mut reservoir = Vec::<&[u8]>::with_capacity(num_samples.min(self.len() / value_width));
+        for (index, value) in self.chunks_exact(value_width).enumerate() {
+            if reservoir.len() < num_samples {
+                reservoir.push(value);
+            } else {
+                let slot = rng.gen_range(0..=index);
+                if slot < num_samples {
+                    reservoir[slot] = value;
+                }
+            }
+        }
+        Self::Owned(reservoir.concat())
+    }
+
+    /// Converts the buffer, interpreted as native-endian values of `T`, into an `Arc<[T]>`
+    ///
+    /// This always copies since an `Arc<[T]>` stores its reference counts in the same
+    /// allocation as the values.  Only a single copy is made, even if the buffer is not aligned
+    /// for `T`.  See [`Self::reinterpret_slice`] for the reverse (zero-copy) conversion.
+    ///
+    /// Returns an error if the buffer length is not a multiple of the size of `T`.
+    pub fn into_arc_typed_slice<T: ArrowNativeType>(self) -> Result<Arc<[T]>> {
+        let value_size = std::mem::size_of::<T>();
+        self.check_value_width(value_size)?;
+        // SAFETY: ArrowNativeType is only implemented for plain-old-data types where
+        // every bit pattern is a valid value
+        let (prefix, values, suffix) = unsafe { self.as_ref().align_to::<T>() };
+        if prefix.is_empty() && suffix.is_empty() {
+            return Ok(Arc::from(values));
+        }
+        // The values are read straight into the Arc instead of going through typed_values,
+        // which would copy them into an aligned Vec first.  The iterator has an exact length so
+        // the Arc is allocated once.
+        Ok(self
+            .chunks_exact(value_size)
+            // SAFETY: each chunk is size_of::<T>() bytes and every bit pattern is a valid T
+            .map(|chunk| unsafe { std::ptr::read_unaligned(chunk.as_ptr() as *const T) })
+            .collect())
+    }
+
+    /// Reads exactly `len` bytes from `reader` into a new owned buffer
+    ///
+    /// This has the same semantics as [`std::io::Read::read_exact`], if the reader runs out
+    /// of data before `len` bytes have been read then an `UnexpectedEof` error is returned.
+    pub fn from_reader<R: std::io::Read>(reader: &mut R, len: usize) -> std::io::Result<Self> {
+        let mut data = vec![0; len];
+        reader.read_exact(&mut data)?;
+        Ok(Self::Owned(data))
+    }
+
+    /// Returns the rest of the buffer if it starts with `prefix`, otherwise `None`
+    ///
+    /// The remainder is created with [`Self::slice_with_length`] and so is zero-copy for
+    /// borrowed (and shared) buffers.
+    pub fn strip_prefix(&self, prefix: &[u8]) -> Option<Self> {
+        if !self.starts_with(prefix) {
+            return None;
+        }
+        Some(self.slice_with_length(prefix.len(), self.len() - prefix.len()))
+    }
+
+    /// Returns the start of the buffer if it ends with `suffix`, otherwise `None`
+    ///
+    /// See [`Self::strip_prefix`]
+    pub fn strip_suffix(&self, suffix: &[u8]) -> Option<Self> {
+        if !self.ends_with(suffix) {
+            return None;
+        }
+        Some(self.slice_with_length(0, self.len() - suffix.len()))
+    }
+
+    /// Writes the entire buffer to `writer`
+    pub fn write_all_to<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        writer.write_all(self.as_ref())
+    }
+
+    /// Writes all of `buffers`, in order, to `writer` using vectored writes
+    ///
+    /// This allows several buffers to be flushed with a single call (e.g. `writev`) without
+    /// first concatenating them.  Like [`std::io::Write::write_all`] this keeps writing until
+    /// everything has been written and retries on `Interrupted`.
+    pub fn write_vectored_to<W: std::io::Write>(
+        buffers: &[&Self],
+        writer: &mut W,
+    ) -> std::io::Result<()> {
+        let mut slices = buffers
+            .iter()
+            .map(|buffer| std::io::IoSlice::new(buffer.as_ref()))
+            .collect::<Vec<_>>();
+        let mut remaining = slices.as_mut_slice();
+        std::io::IoSlice::advance_slices(&mut remaining, 0);
+        while !remaining.is_empty() {
+            match writer.write_vectored(remaining) {
+                Ok(0) => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::WriteZero,
+                        "failed to write whole buffer",
+                    ));
+                }
+                Ok(written) => std::io::IoSlice::advance_slices(&mut remaining, written),
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds the values of `other` to the values of this buffer, element-wise, in place
+    ///
+    /// Both buffers are interpreted as native-endian values of `T`.  Unlike wrapping or
+    /// saturating arithmetic, an overflow is treated as an error.  Returns an `InvalidInput`
+    /// error naming the index of the first value that would overflow, in which case the
+    /// values are left unchanged.
+    ///
+    /// Returns an error if the buffers have different lengths or their lengths are not a
+    /// multiple of the size of `T`.
+    pub fn checked_add_assign_values<T: ArrowNativeType + CheckedAdd>(
+        &mut self,
+        other: &Self,
+    ) -> Result<()> {
+        if self.len() != other.len() {
+            return Err(Error::invalid_input(
+                format!(
+                    "checked_add_assign_values requires buffers of the same length but received {} and {} bytes",
+                    self.len(),
+                    other.len()
+                ),
+                location!(),
+            ));
+        }
+        let other = other.typed_values::<T>()?;
+        let values = self.as_mut_typed_slice::<T>()?;
+        let sums = values
+            .iter()
+            .zip(other.iter())
+            .enumerate()
+            .map(|(idx, (value, other))| {
+                value.checked_add(other).ok_or_else(|| {
+                    Error::invalid_input(
+                        format!(
+                            "overflow adding {:?} to {:?} at index {}",
+                            other, value, idx
+                        ),
+                        location!(),
+                    )
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        values.copy_from_slice(&sums);
+        Ok(())
+    }
+}
+
+// The maximum number of bits copied at once by copy_bits, chosen so that a chunk shifted by up to
+// 7 bits still fits in a u64
+const COPY_BITS_CHUNK: usize = 56;
+
+// Copies `num_bits` bits from `src` starting at bit `src_offset` into `dest` starting at bit
+// `dest_offset`
+//
+// The destination bits must be unset since they are combined with a bitwise OR
+fn copy_bits(src: &[u8], src_offset: usize, dest: &mut [u8], dest_offset: usize, num_bits: usize) {
+    let mut copied = 0;
+    while copied < num_bits {
+        let chunk_bits = (num_bits - copied).min(COPY_BITS_CHUNK);
+        let value = read_bits(src, src_offset + copied, chunk_bits);
+        write_bits(dest, dest_offset + copied, chunk_bits, value);
+        copied += chunk_bits;
+    }
+}
+
+// Reads `num_bits` (at most COPY_BITS_CHUNK) bits starting at bit `offset`
+fn read_bits(src: &[u8], offset: usize, num_bits: usize) -> u64 {
+    let start = offset / 8;
+    let end = (offset + num_bits).div_ceil(8);
+    let mut word = [0_u8; 8];
+    word[..end - start].copy_from_slice(&src[start..end]);
+    (u64::from_le_bytes(word) >> (offset % 8)) & ((1_u64 << num_bits) - 1)
+}
+
+// ORs the low `num_bits` (at most COPY_BITS_CHUNK) bits of `value` into `dest` starting at bit
+// `offset`
+fn write_bits(dest: &mut [u8], offset: usize, num_bits: usize, value: u64) {
+    let start = offset / 8;
+    let end = (offset + num_bits).div_ceil(8);
+    let shifted = (value << (offset % 8)).to_le_bytes();
+    for (dest, byte) in dest[start..end].iter_mut().zip(shifted) {
+        *dest |= byte;
+    }
+}
+
+// Spreads the bits of a u32 so that bit i moves to bit 2 * i
+fn spread_bits_u32(value: u32) -> u64 {
+    let mut x = value as u64;
+    x = (x | (x << 16)) & 0x0000_FFFF_0000_FFFF;
+    x = (x | (x << 8)) & 0x00FF_00FF_00FF_00FF;
+    x = (x | (x << 4)) & 0x0F0F_0F0F_0F0F_0F0F;
+    x = (x | (x << 2)) & 0x3333_3333_3333_3333;
+    (x | (x << 1)) & 0x5555_5555_5555_5555
+}
+
+// The inverse of spread_bits_u32, gathers the even bits of a u64 into a u32
+fn compact_bits_u64(value: u64) -> u32 {
+    let mut x = value & 0x5555_5555_5555_5555;
+    x = (x | (x >> 1)) & 0x3333_3333_3333_3333;
+    x = (x | (x >> 2)) & 0x0F0F_0F0F_0F0F_0F0F;
+    x = (x | (x >> 4)) & 0x00FF_00FF_00FF_00FF;
+    x = (x | (x >> 8)) & 0x0000_FFFF_0000_FFFF;
+    ((x | (x >> 16)) & 0xFFFF_FFFF) as u32
+}
+
+impl AsRef<[u8]> for LanceBuffer {
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            Self::Borrowed(buffer) => buffer.as_slice(),
+            Self::Owned(buffer) => buffer.as_slice(),
+            Self::Shared(buffer) => buffer,
+            #[cfg(feature = "mmap")]
+            Self::Mapped(mapped) => mapped.buffer.as_slice(),
+        }
+    }
+}
+
+impl Deref for LanceBuffer {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        self.as_ref()
+    }
+}
+
+// All `From` implementations are zero-copy
+
+impl From<Vec<u8>> for LanceBuffer {
+    fn from(buffer: Vec<u8>) -> Self {
+        Self::Owned(buffer)
+    }
+}
+
+impl From<Buffer> for LanceBuffer {
+    fn from(buffer: Buffer) -> Self {
+        Self::Borrowed(buffer)
+    }
+}
+
+// An iterator that keeps a clone of a borrowed LanceBuffer so we
+// can have a 'static lifetime
+pub struct BorrowedBufferIter {
+    buffer: arrow_buffer::Buffer,
+    index: usize,
+}
+
+impl Iterator for BorrowedBufferIter {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.buffer.len() {
+            None
+        } else {
+            // SAFETY: we just checked that index is in bounds
+            let byte = unsafe { self.buffer.get_unchecked(self.index) };
+            self.index += 1;
+            Some(*byte)
+        }
+    }
+}
+
+/// The compression used for the payload of a packed block, see [`LanceBuffer::pack_block`]
+///
+/// The discriminant is the codec id written to the block header.  These ids are part of the
+/// block format and must not change.
+#[cfg(feature = "checksum")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Codec {
+    None = 0,
+    #[cfg(feature = "zstd")]
+    Zstd = 1,
+    #[cfg(feature = "lz4")]
+    Lz4 = 2,
+}
+
+#[cfg(feature = "checksum")]
+impl Codec {
+    /// The codec id written to the block header
+    pub fn id(self) -> u8 {
+        self as u8
+    }
+
+    /// Returns the codec with the given id
+    ///
+    /// Returns an error if the id is unknown or if its codec is not enabled in this build
+    pub fn from_id(id: u8) -> Result<Self> {
+        match id {
+            0 => Ok(Self::None),
+            #[cfg(feature = "zstd")]
+            1 => Ok(Self::Zstd),
+            #[cfg(not(feature = "zstd"))]
+            1 => Err(Error::invalid_input(
+                "codec id 1 in packed block header requires the zstd feature",
+                location!(),
+            )),
+            #[cfg(feature = "lz4")]
+            2 => Ok(Self::Lz4),
+            #[cfg(not(feature = "lz4"))]
+            2 => Err(Error::invalid_input(
+                "codec id 2 in packed block header requires the lz4 feature",
+                location!(),
+            )),
+            _ => Err(Error::invalid_input(
+                format!("unknown codec id {} in packed block header", id),
+                location!(),
+            )),
+        }
+    }
+}
+
+/// An access pattern hint for a memory-mapped buffer, see [`LanceBuffer::advise`]
+#[cfg(feature = "mmap")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Advice {
+    /// The data will be read sequentially, read ahead aggressively (`MADV_SEQUENTIAL`)
+    Sequential,
+    /// The data will be read in random order, read ahead is not useful (`MADV_RANDOM`)
+    Random,
+    /// The data will be read soon, start loading it now (`MADV_WILLNEED`)
+    WillNeed,
+    /// The data will not be read soon, the pages can be dropped (`MADV_DONTNEED`)
+    DontNeed,
+}
+
+/// A view of (part of) a memory-mapped file, see [`LanceBuffer::mmap`]
+///
+/// The mapping is kept next to the Arrow buffer since Arrow buffers do not expose their owner.
+#[cfg(feature = "mmap")]
+#[derive(Clone)]
+pub struct MappedBuffer {
+    // The owner of this buffer is `mmap`
+    buffer: Buffer,
+    mmap: Arc<memmap2::Mmap>,
+}
+
+/// An initialized heap allocation with a caller-chosen alignment
+///
+/// This is used to create buffers that are more strictly aligned than a `Vec<u8>` (or an Arrow
+/// `MutableBuffer`) would be.  Every constructor initializes all `len` bytes before returning.
+/// The memory is freed when the allocation is dropped.
+struct AlignedAllocation {
+    ptr: NonNull<u8>,
+    len: usize,
+    layout: Layout,
+}
+
+// SAFETY: the allocation is uniquely owned and only exposes the memory through &self / &mut self
+unsafe impl Send for AlignedAllocation {}
+// SAFETY: see above, shared references only allow reading the memory
+unsafe impl Sync for AlignedAllocation {}
+
+impl AlignedAllocation {
+    /// Allocates `len` zeroed bytes aligned to `align`
+    ///
+    /// Panics if `align` is not a power of two
+    fn zeroed(len: usize, align: usize) -> Self {
+        Self::allocate(len, align, true)
+    }
+
+    /// Allocates the concatenation of `buffers` aligned to `align`
+    ///
+    /// The memory is not zeroed first since every byte is overwritten by the copies.
+    ///
+    /// Panics if `align` is not a power of two
+    fn concat(buffers: &[LanceBuffer], align: usize) -> Self {
+        let len = buffers.iter().map(|buffer| buffer.len()).sum();
+        let allocation = Self::allocate(len, align, false);
+        let mut offset = 0;
+        for buffer in buffers {
+            // SAFETY: the allocation is the total length of the buffers so each copy is in
+            // bounds, and a new allocation cannot overlap any of the inputs
+            unsafe {
+                std::ptr::copy_nonoverlapping(
+                    buffer.as_ptr(),
+                    allocation.ptr.as_ptr().add(offset),
+                    buffer.len(),
+                )
+            };
+            offset += buffer.len();
+        }
+        debug_assert_eq!(offset, len);
+        allocation
+    }
+
+    /// Allocates `len` bytes aligned to `align`, which are only initialized if `zeroed` is true
+    ///
+    /// The caller must initialize the memory before it is read.
+    fn allocate(len: usize, align: usize, zeroed: bool) -> Self {
+        assert!(
+            align.is_power_of_two(),
+            "alignment must be a power of two but was {}",
+            align
+        );
+        // Zero-sized allocations are not allowed so always allocate at least one byte
+        let layout = Layout::from_size_align(len.max(1), align)
+            .expect("buffer length is too large for the requested alignment");
+        // SAFETY: the layout has a non-zero size
+        let ptr = unsafe {
+            if zeroed {
+                std::alloc::alloc_zeroed(layout)
+            } else {
+                std::alloc::alloc(layout)
+            }
+        };
+        let Some(ptr) = NonNull::new(ptr) else {
+            std::alloc::handle_alloc_error(layout)
+        };
+        Self { ptr, len, layout }
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        // SAFETY: the pointer is valid for len initialized bytes and uniquely owned
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+
+    /// Converts into an Arrow buffer without copying, the buffer takes ownership of the memory
+    fn into_buffer(self) -> Buffer {
+        let (ptr, len) = (self.ptr, self.len);
+        // SAFETY: the pointer is valid for len bytes until the allocation (which is now owned by
+        // the buffer) is dropped and the memory is not mutated after this point
+        unsafe { Buffer::from_custom_allocation(ptr, len, Arc::new(self)) }
+    }
+}
+
+impl Drop for AlignedAllocation {
+    fn drop(&mut self) {
+        // SAFETY: the memory was allocated with this layout in allocate
+        unsafe { std::alloc::dealloc(self.ptr.as_ptr(), self.layout) }
+    }
+}
+
+/// Removes buffers with duplicate contents
+///
+/// Returns the unique buffers, in the order they first appear, and a remap table where entry
+/// `i` is the index in the unique buffers of the contents of `buffers[i]`.  Buffers are
+/// hashed by content so this runs in linear time (in the total number of bytes).
+pub fn deduplicate(buffers: Vec<LanceBuffer>) -> (Vec<LanceBuffer>, Vec<usize>) {
+    let mut remap = Vec::with_capacity(buffers.len());
+    // Maps the content of a buffer to its position in `unique`.  The keys borrow from the
+    // input buffers which are moved into `unique` only after the lookups are done.
+    let mut positions = HashMap::<&[u8], usize>::new();
+    let mut is_first = Vec::with_capacity(buffers.len());
+    for buffer in &buffers {
+        let next = positions.len();
+        let position = *positions.entry(buffer.as_ref()).or_insert(next);
+        is_first.push(position == next);
+        remap.push(position);
+    }
+    drop(positions);
+    let unique = buffers
+        .into_iter()
+        .zip(is_first)
+        .filter_map(|(buffer, is_first)| is_first.then_some(buffer))
+        .collect();
+    (unique, remap)
+}
+
+/// Summary statistics over the values of a LanceBuffer, see [`LanceBuffer::column_stats`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnStats<T> {
+    /// The number of values
+    pub num_values: usize,
+    /// The number of maximal runs of equal values
+    pub num_runs: usize,
+    /// An estimate of the number of distinct values (computed with HyperLogLog)
+    pub num_distinct: u64,
+    /// The smallest value, `None` if there are no values
+    pub min: Option<T>,
+    /// The largest value, `None` if there are no values
+    pub max: Option<T>,
+    /// True if there is at least one value and every value is the same
+    pub is_constant: bool,
+}
+
+/// A consuming iterator over the values of a LanceBuffer, see [`LanceBuffer::into_typed_iter`]
+pub struct TypedBufferIter<T: ArrowNativeType> {
+    values: ScalarBuffer<T>,
+    // The values in start..end have not been yielded yet
+    start: usize,
+    end: usize,
+}
+
+impl<T: ArrowNativeType> Iterator for TypedBufferIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.start >= self.end {
+            None
+        } else {
+            let value = self.values[self.start];
+            self.start += 1;
+            Some(value)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.end - self.start;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<T: ArrowNativeType> DoubleEndedIterator for TypedBufferIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.start >= self.end {
+            None
+        } else {
+            self.end -= 1;
+            Some(self.values[self.end])
+        }
+    }
+}
+
+impl<T: ArrowNativeType> ExactSizeIterator for TypedBufferIter<T> {}
+
+impl IntoIterator for LanceBuffer {
+    type Item = u8;
+    type IntoIter = Either<std::vec::IntoIter<u8>, BorrowedBufferIter>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        match self {
+            Self::Borrowed(buffer) => Either::Right(BorrowedBufferIter { buffer, index: 0 }),
+            Self::Owned(buffer) => Either::Left(buffer.into_iter()),
+            Self::Shared(buffer) => Either::Right(BorrowedBufferIter {
+                buffer: Self::shared_to_arrow(buffer),
+                index: 0,
+            }),
+            #[cfg(feature = "mmap")]
+            Self::Mapped(mapped) => Either::Right(BorrowedBufferIter {
+                buffer: mapped.buffer,
+                index: 0,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{ptr::NonNull, sync::Arc};
+
+    use arrow_array::Array;
+    use arrow_buffer::Buffer;
+    use rand::{Rng, RngCore, SeedableRng};
+
+    use super::LanceBuffer;
+
+    #[test]
     fn test_eq() {
         let buf = LanceBuffer::Borrowed(Buffer::from_vec(vec![1_u8, 2, 3]));
         let buf2 = LanceBuffer::Owned(vec![1, 2, 3]);
@@ -495,113 +3956,2793 @@ mod tests {
     }
 
     #[test]
-    fn test_reinterpret_vec() {
-        let vec = vec![1_u32, 2, 3];
-        let mut buf = LanceBuffer::reinterpret_vec(vec);
+    fn test_reinterpret_vec() {
+        let vec = vec![1_u32, 2, 3];
+        let mut buf = LanceBuffer::reinterpret_vec(vec);
+
+        let mut expected = Vec::with_capacity(12);
+        expected.extend_from_slice(&1_u32.to_ne_bytes());
+        expected.extend_from_slice(&2_u32.to_ne_bytes());
+        expected.extend_from_slice(&3_u32.to_ne_bytes());
+        let expected = LanceBuffer::Owned(expected);
+
+        assert_eq!(expected, buf);
+        assert_eq!(buf.borrow_to_typed_slice::<u32>().as_ref(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_concat() {
+        let buf1 = LanceBuffer::Owned(vec![1_u8, 2, 3]);
+        let buf2 = LanceBuffer::Owned(vec![4_u8, 5, 6]);
+        let buf3 = LanceBuffer::Owned(vec![7_u8, 8, 9]);
+
+        let expected = LanceBuffer::Owned(vec![1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        assert_eq!(
+            expected,
+            LanceBuffer::concat_into_one(vec![buf1, buf2, buf3])
+        );
+
+        let empty = LanceBuffer::empty();
+        assert_eq!(
+            LanceBuffer::empty(),
+            LanceBuffer::concat_into_one(vec![empty])
+        );
+
+        let expected = LanceBuffer::Owned(vec![1, 2, 3]);
+        assert_eq!(
+            expected,
+            LanceBuffer::concat_into_one(vec![expected.deep_copy(), LanceBuffer::empty()])
+        );
+    }
+
+    #[test]
+    fn test_zip() {
+        let buf1 = LanceBuffer::Owned(vec![1_u8, 2, 3]);
+        let buf2 = LanceBuffer::reinterpret_vec(vec![1_u16, 2, 3]);
+        let buf3 = LanceBuffer::reinterpret_vec(vec![1_u32, 2, 3]);
+
+        let zipped = LanceBuffer::zip_into_one(vec![(buf1, 8), (buf2, 16), (buf3, 32)], 3).unwrap();
+
+        assert_eq!(zipped.len(), 21);
+
+        let mut expected = Vec::with_capacity(21);
+        for i in 1..4 {
+            expected.push(i as u8);
+            expected.extend_from_slice(&(i as u16).to_ne_bytes());
+            expected.extend_from_slice(&(i as u32).to_ne_bytes());
+        }
+        let expected = LanceBuffer::Owned(expected);
+
+        assert_eq!(expected, zipped);
+    }
+
+    #[test]
+    fn test_hex() {
+        let buf = LanceBuffer::Owned(vec![1, 2, 15, 20]);
+        assert_eq!("01020F14", buf.as_hex());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_to_typed_slice_invalid() {
+        let mut buf = LanceBuffer::Owned(vec![0, 1, 2]);
+        buf.borrow_to_typed_slice::<u16>();
+    }
+
+    #[test]
+    fn test_to_typed_slice() {
+        // Buffer is aligned, no copy will be made, both calls
+        // should get same ptr
+        let mut buf = LanceBuffer::Owned(vec![0, 1]);
+        let borrow = buf.borrow_to_typed_slice::<u16>();
+        let view_ptr = borrow.as_ref().as_ptr();
+        let borrow2 = buf.borrow_to_typed_slice::<u16>();
+        let view_ptr2 = borrow2.as_ref().as_ptr();
+
+        assert_eq!(view_ptr, view_ptr2);
+
+        let bytes = bytes::Bytes::from(vec![0, 1, 2]);
+        let sliced = bytes.slice(1..3);
+        // Intentionally LYING about alignment here to trigger test
+        let mut buf = LanceBuffer::from_bytes(sliced, 1);
+        let borrow = buf.borrow_to_typed_slice::<u16>();
+        let view_ptr = borrow.as_ref().as_ptr();
+        let borrow2 = buf.borrow_to_typed_slice::<u16>();
+        let view_ptr2 = borrow2.as_ref().as_ptr();
+
+        assert_ne!(view_ptr, view_ptr2);
+    }
+
+    #[test]
+    fn test_bit_slice_le() {
+        let mut buf = LanceBuffer::Owned(vec![0x0F, 0x0B]);
+
+        // Keep in mind that validity buffers are *bitwise* little-endian
+        assert_eq!(buf.bit_slice_le_with_length(0, 4).as_ref(), &[0x0F]);
+        assert_eq!(buf.bit_slice_le_with_length(4, 4).as_ref(), &[0x00]);
+        assert_eq!(buf.bit_slice_le_with_length(3, 8).as_ref(), &[0x61]);
+        assert_eq!(buf.bit_slice_le_with_length(0, 8).as_ref(), &[0x0F]);
+        assert_eq!(buf.bit_slice_le_with_length(4, 8).as_ref(), &[0xB0]);
+        assert_eq!(buf.bit_slice_le_with_length(4, 12).as_ref(), &[0xB0, 0x00]);
+    }
+
+    #[test]
+    fn test_abs_neg_values() {
+        let buf = LanceBuffer::reinterpret_vec(vec![-5_i32, 0, 7, i32::MIN, i32::MAX]);
+
+        let abs = buf.abs_values::<i32>().unwrap();
+        assert_eq!(
+            abs,
+            LanceBuffer::reinterpret_vec(vec![5_i32, 0, 7, i32::MIN, i32::MAX])
+        );
+
+        let neg = buf.neg_values::<i32>().unwrap();
+        assert_eq!(
+            neg,
+            LanceBuffer::reinterpret_vec(vec![5_i32, 0, -7, i32::MIN, -i32::MAX])
+        );
+
+        let buf = LanceBuffer::reinterpret_vec(vec![-1_i8, i8::MIN, 3]);
+        assert_eq!(
+            buf.abs_values::<i8>().unwrap(),
+            LanceBuffer::reinterpret_vec(vec![1_i8, i8::MIN, 3])
+        );
+
+        let buf = LanceBuffer::Owned(vec![0, 1, 2]);
+        assert!(buf.abs_values::<i16>().is_err());
+        assert!(buf.neg_values::<i16>().is_err());
+    }
+
+    #[test]
+    fn test_clamp_values() {
+        let buf = LanceBuffer::reinterpret_vec(vec![-100_i32, -10, 0, 5, 10, 1000]);
+
+        let clamped = buf.clamp_values::<i32>(-10, 10).unwrap();
+        assert_eq!(
+            clamped,
+            LanceBuffer::reinterpret_vec(vec![-10_i32, -10, 0, 5, 10, 10])
+        );
+
+        assert!(buf.clamp_values::<i32>(10, -10).is_err());
+        assert!(LanceBuffer::Owned(vec![0; 3])
+            .clamp_values::<i32>(0, 1)
+            .is_err());
+    }
+
+    #[test]
+    fn test_from_typed_slice() {
+        let vec = vec![1_u32, 2, 3, u32::MAX];
+        let buf = LanceBuffer::from_typed_slice(vec.as_slice());
+        assert!(matches!(buf, LanceBuffer::Owned(_)));
+        assert_eq!(buf, LanceBuffer::reinterpret_vec(vec));
+
+        let empty: &[u64] = &[];
+        assert_eq!(LanceBuffer::from_typed_slice(empty).len(), 0);
+    }
+
+    #[test]
+    fn test_morton() {
+        let x = LanceBuffer::reinterpret_vec(vec![0_u32, 1, 0, 3, u32::MAX, 0, 12345]);
+        let y = LanceBuffer::reinterpret_vec(vec![0_u32, 0, 1, 5, 0, u32::MAX, 67890]);
+
+        let codes = LanceBuffer::morton_encode_u32(&x, &y).unwrap();
+        let codes_view = codes.typed_values::<u64>().unwrap().into_owned();
+        assert_eq!(
+            &codes_view[..6],
+            &[
+                0,
+                1,
+                2,
+                0b100111,
+                0x5555_5555_5555_5555,
+                0xAAAA_AAAA_AAAA_AAAA
+            ]
+        );
+
+        let (decoded_x, decoded_y) = codes.morton_decode_u64().unwrap();
+        assert_eq!(decoded_x, x);
+        assert_eq!(decoded_y, y);
+
+        let short = LanceBuffer::reinterpret_vec(vec![1_u32]);
+        assert!(LanceBuffer::morton_encode_u32(&x, &short).is_err());
+        assert!(LanceBuffer::Owned(vec![0; 7]).morton_decode_u64().is_err());
+    }
+
+    #[test]
+    fn test_into_fixed_size_binary() {
+        let buf = LanceBuffer::Owned((0..12).collect());
+        let array = buf.into_fixed_size_binary(4).unwrap();
+        assert_eq!(array.len(), 3);
+        assert_eq!(array.value_length(), 4);
+        assert_eq!(array.value(0), &[0, 1, 2, 3]);
+        assert_eq!(array.value(2), &[8, 9, 10, 11]);
+
+        let buf = LanceBuffer::Owned((0..10).collect());
+        assert!(buf.into_fixed_size_binary(4).is_err());
+        assert!(LanceBuffer::empty().into_fixed_size_binary(0).is_err());
+    }
+
+    #[test]
+    fn test_push_value() {
+        let mut buf = LanceBuffer::empty();
+        for value in [1_u32, 2, 3, u32::MAX] {
+            buf.push_value(value);
+        }
+        assert_eq!(
+            buf,
+            LanceBuffer::reinterpret_vec(vec![1_u32, 2, 3, u32::MAX])
+        );
+
+        // Borrowed buffers are switched to owned mode
+        let mut buf = LanceBuffer::reinterpret_vec(vec![1_u16]);
+        let mut clone = buf.borrow_and_clone();
+        buf.push_value(2_u16);
+        assert!(matches!(buf, LanceBuffer::Owned(_)));
+        assert_eq!(buf, LanceBuffer::reinterpret_vec(vec![1_u16, 2]));
+        assert_eq!(clone.borrow_to_typed_slice::<u16>().as_ref(), &[1]);
+    }
+
+    #[test]
+    fn test_concat_optional_bitmaps() {
+        assert!(LanceBuffer::concat_optional_bitmaps(&[(None, 3), (None, 10)]).is_none());
+        assert!(LanceBuffer::concat_optional_bitmaps(&[]).is_none());
+
+        let first = LanceBuffer::Owned(vec![0b0000_0101]);
+        let third = LanceBuffer::Owned(vec![0b1111_1110, 0b0000_0001]);
+        let (bitmap, num_bits) = LanceBuffer::concat_optional_bitmaps(&[
+            (Some(&first), 3),
+            (None, 4),
+            (Some(&third), 9),
+        ])
+        .unwrap();
+        assert_eq!(num_bits, 16);
+        // 101 + 1111 + 011111111 (bit 0 first)
+        assert_eq!(bitmap.as_ref(), &[0b0111_1101, 0b1111_1111]);
+    }
+
+    #[test]
+    fn test_rotate_bytes() {
+        let mut buf = LanceBuffer::Owned(vec![1, 2, 3, 4]);
+        buf.rotate_bytes_left(1);
+        assert_eq!(buf.as_ref(), &[2, 3, 4, 1]);
+        buf.rotate_bytes_right(1);
+        assert_eq!(buf.as_ref(), &[1, 2, 3, 4]);
+        buf.rotate_bytes_right(5);
+        assert_eq!(buf.as_ref(), &[4, 1, 2, 3]);
+
+        let mut buf = LanceBuffer::Borrowed(Buffer::from_vec(vec![1_u8, 2, 3, 4]));
+        buf.rotate_bytes_left(6);
+        assert_eq!(buf.as_ref(), &[3, 4, 1, 2]);
+
+        let mut empty = LanceBuffer::empty();
+        empty.rotate_bytes_left(3);
+        empty.rotate_bytes_right(3);
+        assert_eq!(empty.len(), 0);
+    }
+
+    #[test]
+    fn test_should_compress() {
+        let compressible = (0..100_000).map(|i| (i % 4) as u8).collect::<Vec<_>>();
+        let compressible = LanceBuffer::Owned(compressible);
+        // 4 equally likely values is exactly 2 bits of entropy
+        assert!(compressible.should_compress(2.5));
+        assert!(!compressible.should_compress(1.5));
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+        let mut random = vec![0_u8; 100_000];
+        rng.fill_bytes(&mut random);
+        let random = LanceBuffer::Owned(random);
+        assert!(!random.should_compress(7.0));
+
+        let small = LanceBuffer::Owned(vec![0; 10]);
+        assert!(small.should_compress(1.0));
+        assert!(!LanceBuffer::empty().should_compress(1.0));
+    }
+
+    #[cfg(target_endian = "little")]
+    #[test]
+    fn test_ensure_little_endian() {
+        let mut buf = LanceBuffer::reinterpret_vec(vec![1_u32, 0x01020304]);
+        let ptr = buf.as_ptr();
+        buf.ensure_little_endian::<u32>();
+        assert_eq!(buf.as_ref(), &[1, 0, 0, 0, 4, 3, 2, 1]);
+        // No copy should be made on little-endian targets
+        assert_eq!(buf.as_ptr(), ptr);
+        assert!(matches!(buf, LanceBuffer::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_map_chunks_in_place() {
+        let mut buf = LanceBuffer::Owned(vec![1, 2, 3, 4, 5]);
+        let mut chunk_lens = Vec::new();
+        buf.map_chunks_in_place(2, |chunk| {
+            chunk_lens.push(chunk.len());
+            for byte in chunk.iter_mut() {
+                *byte = byte.wrapping_neg();
+            }
+        });
+        assert_eq!(chunk_lens, vec![2, 2, 1]);
+        assert_eq!(buf.as_ref(), &[255, 254, 253, 252, 251]);
+
+        // Chunks can be transformed as a whole (e.g. swapping the bytes of each pair)
+        let mut buf = LanceBuffer::reinterpret_vec(vec![0x0102_u16, 0x0304]);
+        buf.map_chunks_in_place(2, |chunk| chunk.reverse());
+        assert_eq!(buf, LanceBuffer::reinterpret_vec(vec![0x0201_u16, 0x0403]));
+    }
+
+    #[test]
+    fn test_from_bytes_threshold() {
+        let source = bytes::Bytes::from(vec![0_u8; 1024]);
+
+        let small = source.slice(0..8);
+        let buf = LanceBuffer::from_bytes_threshold(small, 1, 16);
+        assert!(matches!(buf, LanceBuffer::Owned(_)));
+        assert_eq!(buf.as_ref(), &[0; 8]);
+        // The copy does not keep the source alive
+        assert!(source.is_unique());
+
+        let large = source.slice(0..512);
+        let buf = LanceBuffer::from_bytes_threshold(large, 1, 16);
+        assert!(matches!(buf, LanceBuffer::Borrowed(_)));
+        assert_eq!(buf.len(), 512);
+        assert!(!source.is_unique());
+        drop(buf);
+        assert!(source.is_unique());
+    }
+
+    #[test]
+    fn test_set_bit_indices() {
+        let mut bytes = vec![0_u8; 20];
+        let expected = vec![0_u32, 3, 9, 63, 64, 100, 150];
+        for &index in &expected {
+            bytes[index as usize / 8] |= 1 << (index % 8);
+        }
+        // This bit is past len_bits and should be ignored
+        bytes[19] |= 0x80;
+        let buf = LanceBuffer::Owned(bytes);
+        assert_eq!(buf.set_bit_indices(155), expected);
+        assert_eq!(buf.set_bit_indices(64), vec![0, 3, 9, 63]);
+
+        let zeros = LanceBuffer::all_unset(16);
+        assert!(zeros.set_bit_indices(128).is_empty());
+    }
+
+    #[test]
+    fn test_concat_skipping_empty() {
+        let make_buffers = || {
+            vec![
+                LanceBuffer::empty(),
+                LanceBuffer::Owned(vec![1, 2]),
+                LanceBuffer::empty(),
+                LanceBuffer::Owned(vec![3]),
+                LanceBuffer::empty(),
+            ]
+        };
+        let expected = LanceBuffer::concat_into_one(
+            make_buffers()
+                .into_iter()
+                .filter(|buf| !buf.is_empty())
+                .collect(),
+        );
+        assert_eq!(LanceBuffer::concat_skipping_empty(make_buffers()), expected);
+
+        // A single non-empty borrowed buffer is returned without a copy
+        let borrowed = LanceBuffer::Borrowed(Buffer::from_vec(vec![4_u8, 5, 6]));
+        let ptr = borrowed.as_ptr();
+        let result = LanceBuffer::concat_skipping_empty(vec![
+            LanceBuffer::empty(),
+            borrowed,
+            LanceBuffer::empty(),
+        ]);
+        assert!(matches!(result, LanceBuffer::Borrowed(_)));
+        assert_eq!(result.as_ptr(), ptr);
+
+        let all_empty = vec![LanceBuffer::empty(), LanceBuffer::empty()];
+        assert_eq!(LanceBuffer::concat_skipping_empty(all_empty).len(), 0);
+    }
+
+    #[test]
+    fn test_reverse_bits_per_byte() {
+        let buf = LanceBuffer::Owned(vec![0b0000_0001, 0b1100_1010, 0xFF, 0x00, 0b0001_0110]);
+        let reversed = buf.reverse_bits_per_byte();
+        assert_eq!(
+            reversed.as_ref(),
+            &[0b1000_0000, 0b0101_0011, 0xFF, 0x00, 0b0110_1000]
+        );
+        assert_eq!(reversed.reverse_bits_per_byte(), buf);
+    }
+
+    #[test]
+    fn test_grow_bitmap() {
+        let mut buf = LanceBuffer::Borrowed(Buffer::from_vec(vec![0b0001_0101_u8]));
+        buf.grow_bitmap(5, 12, true);
+        assert_eq!(buf.len(), 2);
+        // Old bits are preserved, bits 5..12 are set, bits past 12 are unset
+        assert_eq!(buf.as_ref(), &[0b1111_0101, 0b0000_1111]);
+
+        buf.grow_bitmap(12, 30, false);
+        assert_eq!(buf.as_ref(), &[0b1111_0101, 0b0000_1111, 0, 0]);
+
+        let mut buf = LanceBuffer::empty();
+        buf.grow_bitmap(0, 20, true);
+        assert_eq!(buf.as_ref(), &[0xFF, 0xFF, 0x0F]);
+
+        // Existing garbage past old_len_bits is overwritten
+        let mut buf = LanceBuffer::Owned(vec![0xFF]);
+        buf.grow_bitmap(3, 6, false);
+        assert_eq!(buf.as_ref(), &[0b1100_0111]);
+    }
+
+    #[test]
+    fn test_checked_get() {
+        let buf = LanceBuffer::Owned(vec![1, 2, 3, 4]);
+        assert_eq!(buf.get(0), Some(1));
+        assert_eq!(buf.get(3), Some(4));
+        assert_eq!(buf.get(4), None);
+        assert_eq!(buf.get(usize::MAX), None);
+
+        assert_eq!(buf.get_range(1..3), Some([2_u8, 3].as_slice()));
+        assert_eq!(buf.get_range(0..4), Some([1_u8, 2, 3, 4].as_slice()));
+        assert_eq!(buf.get_range(4..4), Some([].as_slice()));
+        assert_eq!(buf.get_range(2..5), None);
+        #[allow(clippy::reversed_empty_ranges)]
+        let reversed = buf.get_range(3..1);
+        assert_eq!(reversed, None);
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_estimate_compressed_size_zstd() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+        // Compressible data, a small alphabet of values
+        let data = (0..1024 * 1024)
+            .map(|_| rng.gen_range(0..4_u8))
+            .collect::<Vec<_>>();
+        let buf = LanceBuffer::Owned(data);
+
+        let actual = zstd::bulk::compress(&buf, 3).unwrap().len();
+        let estimate = buf.estimate_compressed_size_zstd(3, 64 * 1024);
+        assert!(estimate < buf.len() / 2);
+        assert!(
+            estimate > actual / 2 && estimate < actual * 2,
+            "estimate {} is too far from actual {}",
+            estimate,
+            actual
+        );
+
+        // Small buffers are compressed in full
+        let small = LanceBuffer::Owned(vec![7; 100]);
+        let exact = zstd::bulk::compress(&small, 3).unwrap().len();
+        assert_eq!(small.estimate_compressed_size_zstd(3, 1024), exact);
+
+        assert_eq!(
+            LanceBuffer::empty().estimate_compressed_size_zstd(3, 1024),
+            0
+        );
+    }
+
+    #[test]
+    fn test_byte_diff_count() {
+        let left = LanceBuffer::Owned((0..20).collect());
+        let mut right = (0..20).collect::<Vec<u8>>();
+        right[0] = 100;
+        right[9] = 100;
+        right[19] = 100;
+        let right = LanceBuffer::Owned(right);
+        assert_eq!(left.byte_diff_count(&right).unwrap(), 3);
+        assert_eq!(left.byte_diff_count(&left).unwrap(), 0);
+
+        assert!(left
+            .byte_diff_count(&LanceBuffer::Owned(vec![0; 3]))
+            .is_err());
+        assert_eq!(
+            LanceBuffer::empty()
+                .byte_diff_count(&LanceBuffer::empty())
+                .unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_as_mut_typed_slice() {
+        let mut buf = LanceBuffer::reinterpret_vec(vec![1_u32, 2, 3]);
+        for value in buf.as_mut_typed_slice::<u32>().unwrap() {
+            *value += 1;
+        }
+        assert!(matches!(buf, LanceBuffer::Owned(_)));
+        assert_eq!(buf, LanceBuffer::reinterpret_vec(vec![2_u32, 3, 4]));
+
+        // Unaligned data is realigned
+        let bytes = bytes::Bytes::from(vec![0_u8, 1, 0, 2, 0]);
+        let mut buf = LanceBuffer::from_bytes(bytes.slice(1..5), 1);
+        buf.as_mut_typed_slice::<u16>().unwrap()[1] = 7;
+        assert_eq!(buf, LanceBuffer::reinterpret_vec(vec![1_u16, 7]));
+
+        let mut buf = LanceBuffer::Owned(vec![0; 3]);
+        assert!(buf.as_mut_typed_slice::<u16>().is_err());
+    }
+
+    #[test]
+    fn test_take_ranges() {
+        let buf = LanceBuffer::Owned((0..10).collect());
+        let taken = buf.take_ranges(&[(0, 2), (5, 3)]).unwrap();
+        assert_eq!(taken.as_ref(), &[0, 1, 5, 6, 7]);
+
+        let taken = buf.take_ranges(&[(8, 2), (0, 1), (9, 0)]).unwrap();
+        assert_eq!(taken.as_ref(), &[8, 9, 0]);
+
+        assert_eq!(buf.take_ranges(&[]).unwrap().len(), 0);
+        assert!(buf.take_ranges(&[(0, 2), (9, 2)]).is_err());
+        assert!(buf.take_ranges(&[(usize::MAX, 2)]).is_err());
+    }
+
+    #[test]
+    fn test_into_typed_iter() {
+        let buf = LanceBuffer::reinterpret_vec(vec![1_u16, 2, 3, 4]);
+        let mut iter = buf.into_typed_iter::<u16>().unwrap();
+        assert_eq!(iter.len(), 4);
+        assert_eq!(iter.next(), Some(1));
+        assert_eq!(iter.next_back(), Some(4));
+        assert_eq!(iter.len(), 2);
+        assert_eq!(iter.rev().collect::<Vec<_>>(), vec![3, 2]);
+
+        let buf = LanceBuffer::reinterpret_vec(vec![1_u16, 2, 3]);
+        assert_eq!(
+            buf.into_typed_iter::<u16>()
+                .unwrap()
+                .rev()
+                .collect::<Vec<_>>(),
+            vec![3, 2, 1]
+        );
+
+        assert!(LanceBuffer::Owned(vec![0; 3])
+            .into_typed_iter::<u16>()
+            .is_err());
+    }
+
+    #[cfg(feature = "checksum")]
+    #[test]
+    fn test_concat_and_crc32c() {
+        let buffers = vec![
+            LanceBuffer::Owned(vec![1, 2, 3]),
+            LanceBuffer::empty(),
+            LanceBuffer::reinterpret_vec(vec![4_u32, 5]),
+        ];
+        let (combined, crc) = LanceBuffer::concat_and_crc32c(&buffers);
+        let expected = LanceBuffer::concat(&buffers);
+        assert_eq!(combined, expected);
+        assert_eq!(crc, crc32c::crc32c(&expected));
+
+        let (empty, crc) = LanceBuffer::concat_and_crc32c(&[]);
+        assert_eq!(empty.len(), 0);
+        assert_eq!(crc, 0);
+    }
+
+    #[test]
+    fn test_split_by_delimiter() {
+        let split = |data: &[u8]| {
+            LanceBuffer::copy_slice(data)
+                .split_by_delimiter(b'\n')
+                .into_iter()
+                .map(|piece| piece.into_owned())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            split(b"ab\ncd\n\ne"),
+            vec![b"ab".to_vec(), b"cd".to_vec(), vec![], b"e".to_vec()]
+        );
+        assert_eq!(split(b"\nab\n"), vec![vec![], b"ab".to_vec(), vec![]]);
+        assert_eq!(split(b"abc"), vec![b"abc".to_vec()]);
+        assert_eq!(split(b""), vec![Vec::<u8>::new()]);
+
+        // Borrowed buffers are split without copying
+        let buf = LanceBuffer::Borrowed(Buffer::from_vec(b"ab\ncd".to_vec()));
+        let pieces = buf.split_by_delimiter(b'\n');
+        assert_eq!(pieces.len(), 2);
+        assert_eq!(pieces[0].as_ptr(), buf.as_ptr());
+        assert_eq!(pieces[1].as_ptr(), buf[3..].as_ptr());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_to_json_value() {
+        let buf = LanceBuffer::Owned(vec![1, 2, 15, 20]);
+        assert_eq!(
+            buf.to_json_value(4),
+            serde_json::json!({ "inline": "01020F14" })
+        );
+        assert_eq!(
+            buf.to_json_value(3),
+            serde_json::json!({ "len": 4, "crc32c": crc32c::crc32c(&[1, 2, 15, 20]) })
+        );
+        assert_eq!(
+            LanceBuffer::empty().to_json_value(0),
+            serde_json::json!({ "inline": "" })
+        );
+    }
+
+    #[test]
+    fn test_reserve() {
+        let mut buf = LanceBuffer::Borrowed(Buffer::from_vec(vec![1_u8, 2, 3]));
+        buf.try_reserve(100).unwrap();
+        assert!(matches!(&buf, LanceBuffer::Owned(data) if data.capacity() >= 103));
+        assert_eq!(buf.as_ref(), &[1, 2, 3]);
+
+        let mut buf = LanceBuffer::empty();
+        buf.reserve_exact(10);
+        assert!(matches!(&buf, LanceBuffer::Owned(data) if data.capacity() >= 10));
+
+        assert!(buf.try_reserve(usize::MAX).is_err());
+    }
+
+    #[test]
+    fn test_count_matching() {
+        let buf = LanceBuffer::reinterpret_vec(vec![1_u32, 50, 7, 100, 1000, 3]);
+        assert_eq!(buf.count_matching::<u32>(|value| value > 10).unwrap(), 3);
+        assert_eq!(buf.count_matching::<u32>(|value| value > 5000).unwrap(), 0);
+        assert_eq!(buf.count_matching::<u16>(|value| value == 0).unwrap(), 6);
+        assert!(LanceBuffer::Owned(vec![0; 6])
+            .count_matching::<u32>(|_| true)
+            .is_err());
+    }
+
+    #[test]
+    fn test_concat_aligned_typed() {
+        // Create unaligned inputs
+        let bytes = bytes::Bytes::from(vec![0_u8; 17]);
+        let unaligned = LanceBuffer::from_bytes(bytes.slice(1..17), 1);
+        assert!(unaligned.as_typed_ref::<u64>().is_none());
+
+        let buffers = vec![
+            unaligned,
+            LanceBuffer::reinterpret_vec(vec![1_u64, 2]),
+            LanceBuffer::empty(),
+        ];
+        let combined = LanceBuffer::concat_aligned_typed::<u64>(&buffers).unwrap();
+        assert_eq!(
+            combined.as_ptr().align_offset(std::mem::align_of::<u64>()),
+            0
+        );
+        assert_eq!(
+            combined.as_typed_ref::<u64>(),
+            Some([0_u64, 0, 1, 2].as_slice())
+        );
+
+        let buffers = vec![
+            LanceBuffer::Owned(vec![0; 8]),
+            LanceBuffer::Owned(vec![0; 3]),
+        ];
+        assert!(LanceBuffer::concat_aligned_typed::<u64>(&buffers).is_err());
+    }
+
+    #[test]
+    fn test_length_prefix() {
+        let small = LanceBuffer::Owned(vec![1, 2, 3]);
+        let prefixed = small.with_length_prefix();
+        assert_eq!(prefixed.as_ref(), &[3, 1, 2, 3]);
+        let (len, body) = prefixed.strip_length_prefix().unwrap();
+        assert_eq!(len, 3);
+        assert_eq!(body, small);
+
+        let large = LanceBuffer::Owned(vec![7; 300]);
+        let prefixed = large.with_length_prefix();
+        // 300 = 0b10_0101100 -> [0b1010_1100, 0b0000_0010]
+        assert_eq!(&prefixed[..2], &[0xAC, 0x02]);
+        assert_eq!(prefixed.len(), 302);
+        let (len, body) = prefixed.strip_length_prefix().unwrap();
+        assert_eq!(len, 300);
+        assert_eq!(body, large);
+
+        let (len, body) = LanceBuffer::empty()
+            .with_length_prefix()
+            .strip_length_prefix()
+            .unwrap();
+        assert_eq!(len, 0);
+        assert_eq!(body.len(), 0);
+
+        // Truncated varint, truncated body, and an overlong varint
+        assert!(LanceBuffer::Owned(vec![0x80])
+            .strip_length_prefix()
+            .is_err());
+        assert!(LanceBuffer::Owned(vec![5, 1, 2])
+            .strip_length_prefix()
+            .is_err());
+        assert!(LanceBuffer::Owned(vec![0xFF; 11])
+            .strip_length_prefix()
+            .is_err());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_par_map_reduce() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+        let mut data = vec![0; 100_003];
+        rng.fill_bytes(&mut data);
+        let expected = data.iter().map(|b| *b as u64).sum::<u64>();
+
+        let buffer = LanceBuffer::Owned(data);
+        let sum = |chunk: &[u8]| chunk.iter().map(|b| *b as u64).sum::<u64>();
+        for chunk_size in [1, 7, 4096, 1_000_000] {
+            assert_eq!(
+                buffer.par_map_reduce(chunk_size, sum, |a, b| a + b, 0),
+                expected
+            );
+        }
+
+        assert_eq!(
+            LanceBuffer::empty().par_map_reduce(8, sum, |a, b| a + b, 0),
+            0
+        );
+    }
+
+    #[test]
+    fn test_swap_record_halves() {
+        let mut buffer = LanceBuffer::Borrowed(Buffer::from_vec(vec![1_u8, 2, 3, 4, 5, 6, 7, 8]));
+        buffer.swap_record_halves(4);
+        assert!(matches!(buffer, LanceBuffer::Owned(_)));
+        assert_eq!(buffer.as_ref(), &[3, 4, 1, 2, 7, 8, 5, 6]);
+
+        // Swapping twice restores the original layout
+        buffer.swap_record_halves(4);
+        assert_eq!(buffer.as_ref(), &[1, 2, 3, 4, 5, 6, 7, 8]);
+
+        buffer.swap_record_halves(2);
+        assert_eq!(buffer.as_ref(), &[2, 1, 4, 3, 6, 5, 8, 7]);
+    }
+
+    #[test]
+    #[should_panic(expected = "positive even number")]
+    fn test_swap_record_halves_odd_width() {
+        LanceBuffer::Owned(vec![0; 6]).swap_record_halves(3);
+    }
+
+    #[test]
+    fn test_from_raw_parts() {
+        let data = (0..100_u8).collect::<Vec<_>>();
+        let ptr = NonNull::new(data.as_ptr() as *mut u8).unwrap();
+        let len = data.len();
+        let owner: Arc<dyn std::any::Any + Send + Sync> = Arc::new(data);
+        let weak = Arc::downgrade(&owner);
+
+        // SAFETY: the Vec is kept alive by the owner and is never mutated
+        let buffer = unsafe { LanceBuffer::from_raw_parts(ptr, len, owner) };
+        assert!(matches!(buffer, LanceBuffer::Borrowed(_)));
+        assert_eq!(buffer.as_ref(), (0..100_u8).collect::<Vec<_>>().as_slice());
+        assert_eq!(buffer.as_ptr(), ptr.as_ptr() as *const u8);
+
+        // The owner lives as long as any clone / slice of the buffer
+        let slice = buffer.slice_with_length(10, 5);
+        drop(buffer);
+        assert!(weak.upgrade().is_some());
+        assert_eq!(slice.as_ref(), &[10, 11, 12, 13, 14]);
+        drop(slice);
+        assert!(weak.upgrade().is_none());
+    }
+
+    #[test]
+    fn test_flip_bits_range() {
+        let mut bitmap = LanceBuffer::Borrowed(Buffer::from_vec(vec![0b1010_0101_u8, 0b1100_0011]));
+        bitmap.flip_bits_range(2, 4);
+        assert!(matches!(bitmap, LanceBuffer::Owned(_)));
+        assert_eq!(bitmap.as_ref(), &[0b1001_1001, 0b1100_0011]);
+
+        // Spanning a byte boundary, including a whole byte
+        let mut bitmap = LanceBuffer::Owned(vec![0x00, 0x00, 0x00]);
+        bitmap.flip_bits_range(4, 16);
+        assert_eq!(bitmap.as_ref(), &[0xF0, 0xFF, 0x0F]);
+
+        // Empty ranges are a no-op
+        bitmap.flip_bits_range(24, 0);
+        assert_eq!(bitmap.as_ref(), &[0xF0, 0xFF, 0x0F]);
+    }
+
+    #[test]
+    fn test_build_dictionary() {
+        let buffer = LanceBuffer::reinterpret_vec(vec![10_u64, 20, 10, 30, 20]);
+        let (mut dictionary, mut indices) = buffer.build_dictionary::<u64>().unwrap();
+        assert_eq!(
+            dictionary.borrow_to_typed_slice::<u64>().as_ref(),
+            &[10, 20, 30]
+        );
+        assert_eq!(
+            indices.borrow_to_typed_slice::<u32>().as_ref(),
+            &[0, 1, 0, 2, 1]
+        );
+
+        let (dictionary, indices) = LanceBuffer::empty().build_dictionary::<u32>().unwrap();
+        assert!(dictionary.is_empty());
+        assert!(indices.is_empty());
+
+        assert!(LanceBuffer::Owned(vec![0; 7])
+            .build_dictionary::<u32>()
+            .is_err());
+    }
+
+    #[test]
+    fn test_truncate_values() {
+        let values = (0..16_u8).collect::<Vec<_>>();
+        let mut borrowed = LanceBuffer::Borrowed(Buffer::from_vec(values.clone()));
+        let ptr = borrowed.as_ptr();
+        borrowed.truncate_values(2, 4);
+        assert!(matches!(borrowed, LanceBuffer::Borrowed(_)));
+        assert_eq!(borrowed.as_ptr(), ptr);
+        assert_eq!(borrowed.as_ref(), &values[..8]);
+
+        let mut owned = LanceBuffer::Owned(values.clone());
+        owned.truncate_values(2, 4);
+        assert_eq!(owned.as_ref(), &values[..8]);
+
+        // Already short enough
+        owned.truncate_values(10, 4);
+        assert_eq!(owned.as_ref(), &values[..8]);
+    }
+
+    #[test]
+    fn test_coalesce_rle_runs() {
+        // Two segments [A x2, B x1] and [B x3, A x1, A x4]
+        let values = LanceBuffer::reinterpret_vec(vec![1_u16, 2, 2, 1, 1]);
+        let run_lengths = LanceBuffer::reinterpret_vec(vec![2_u32, 1, 3, 1, 4]);
+        let (values, mut run_lengths) =
+            LanceBuffer::coalesce_rle_runs(&values, &run_lengths, 2).unwrap();
+        assert_eq!(values, LanceBuffer::reinterpret_vec(vec![1_u16, 2, 1]));
+        assert_eq!(
+            run_lengths.borrow_to_typed_slice::<u32>().as_ref(),
+            &[2, 4, 5]
+        );
+
+        // Runs that would overflow a u32 are not merged
+        let values = LanceBuffer::Owned(vec![7, 7]);
+        let run_lengths = LanceBuffer::reinterpret_vec(vec![u32::MAX, 1]);
+        let (values, mut run_lengths) =
+            LanceBuffer::coalesce_rle_runs(&values, &run_lengths, 1).unwrap();
+        assert_eq!(values.as_ref(), &[7, 7]);
+        assert_eq!(
+            run_lengths.borrow_to_typed_slice::<u32>().as_ref(),
+            &[u32::MAX, 1]
+        );
+
+        let run_lengths = LanceBuffer::reinterpret_vec(vec![1_u32]);
+        assert!(
+            LanceBuffer::coalesce_rle_runs(&LanceBuffer::Owned(vec![1, 2]), &run_lengths, 1)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_to_arrow_buffer_parts() {
+        let mut lance_buffer = LanceBuffer::Owned(vec![1, 2, 3, 4, 5]);
+        let (buffer, offset, len) = lance_buffer.to_arrow_buffer_parts();
+        assert_eq!(offset, 0);
+        assert_eq!(len, lance_buffer.len());
+        assert_eq!(buffer.as_slice(), lance_buffer.as_ref());
+        // The returned buffer shares memory with the (now borrowed) LanceBuffer
+        assert!(matches!(lance_buffer, LanceBuffer::Borrowed(_)));
+        assert_eq!(buffer.as_ptr(), lance_buffer.as_ptr());
+    }
+
+    #[test]
+    fn test_reverse_bitmap() {
+        let bitmap = LanceBuffer::Owned(vec![0b0000_0011]);
+        assert_eq!(bitmap.reverse_bitmap(4).as_ref(), &[0b0000_1100]);
+        assert_eq!(bitmap.reverse_bitmap(8).as_ref(), &[0b1100_0000]);
+
+        // Spanning bytes with a partial final byte
+        let bitmap = LanceBuffer::Owned(vec![0b0000_0001, 0b0000_0110]);
+        assert_eq!(
+            bitmap.reverse_bitmap(11).as_ref(),
+            &[0b0000_0011, 0b0000_0100]
+        );
+
+        assert!(LanceBuffer::empty().reverse_bitmap(0).is_empty());
+    }
+
+    #[test]
+    fn test_write_hex_into() {
+        let buffer = LanceBuffer::Owned(vec![0x00, 0x0A, 0xBC, 0xFF, 0x12]);
+        let mut out = String::with_capacity(64);
+        let capacity = out.capacity();
+        buffer.write_hex_into(&mut out);
+        assert_eq!(out, buffer.as_hex());
+        assert_eq!(out.capacity(), capacity);
+
+        // Appends rather than overwriting
+        LanceBuffer::Owned(vec![0x34]).write_hex_into(&mut out);
+        assert_eq!(out, "000ABCFF1234");
+    }
+
+    #[test]
+    fn test_split_soa() {
+        let widths = [1, 4, 2];
+        let records = (0..5_u8)
+            .flat_map(|i| {
+                let mut record = vec![i];
+                record.extend_from_slice(&(i as u32 * 1000).to_le_bytes());
+                record.extend_from_slice(&(i as u16 + 7).to_le_bytes());
+                record
+            })
+            .collect::<Vec<_>>();
+        let buffer = LanceBuffer::Owned(records);
+
+        let mut fields = buffer.split_soa(&widths, 5).unwrap();
+        assert_eq!(fields.len(), 3);
+        assert_eq!(fields[0].as_ref(), &[0, 1, 2, 3, 4]);
+        assert_eq!(
+            fields[1].borrow_to_typed_slice::<u32>().as_ref(),
+            &[0, 1000, 2000, 3000, 4000]
+        );
+        assert_eq!(
+            fields[2].borrow_to_typed_slice::<u16>().as_ref(),
+            &[7, 8, 9, 10, 11]
+        );
+
+        // Zipping the fields back together restores the original records
+        let zipped = LanceBuffer::zip_into_one(
+            fields
+                .into_iter()
+                .zip(widths)
+                .map(|(field, width)| (field, width as u64 * 8))
+                .collect(),
+            5,
+        )
+        .unwrap();
+        assert_eq!(zipped, buffer);
+
+        assert!(buffer.split_soa(&widths, 4).is_err());
+    }
+
+    #[test]
+    fn test_count_leading_equal_bytes() {
+        let records = [
+            LanceBuffer::Owned(vec![1, 2, 3, 4, 5]),
+            LanceBuffer::Owned(vec![1, 2, 3, 9, 5]),
+            LanceBuffer::Borrowed(Buffer::from_vec(vec![1_u8, 2, 3, 4, 0])),
+        ];
+        assert_eq!(LanceBuffer::count_leading_equal_bytes(&records), 3);
+        assert_eq!(LanceBuffer::count_leading_equal_bytes(&records[..1]), 5);
+        assert_eq!(LanceBuffer::count_leading_equal_bytes(&[]), 0);
+
+        let records = [
+            LanceBuffer::Owned(vec![1, 2]),
+            LanceBuffer::Owned(vec![2, 2]),
+        ];
+        assert_eq!(LanceBuffer::count_leading_equal_bytes(&records), 0);
+    }
+
+    #[test]
+    fn test_runs() {
+        let buffer = LanceBuffer::Owned(vec![7, 7, 9]);
+        assert_eq!(
+            buffer.runs(1).collect::<Vec<_>>(),
+            vec![(&[7_u8][..], 2), (&[9][..], 1)]
+        );
+
+        let buffer = LanceBuffer::reinterpret_vec(vec![1_u16, 1, 1, 2, 1, 1]);
+        assert_eq!(
+            buffer.runs(2).map(|(_, len)| len).collect::<Vec<_>>(),
+            vec![3, 1, 2]
+        );
+
+        assert_eq!(LanceBuffer::empty().runs(4).count(), 0);
+    }
+
+    #[test]
+    fn test_rank() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+        let mut bitmap = vec![0; 100];
+        rng.fill_bytes(&mut bitmap);
+        let bitmap = LanceBuffer::Owned(bitmap);
+        let len_bits = 797;
+
+        for block_bits in [1, 7, 64, 100, 1000] {
+            let index = bitmap.build_rank_index(len_bits, block_bits);
+            assert_eq!(index.len(), len_bits / block_bits + 1);
+            let mut expected = 0;
+            for pos in 0..=len_bits {
+                assert_eq!(bitmap.rank(&index, block_bits, pos), expected);
+                if pos < len_bits && arrow_buffer::bit_util::get_bit(&bitmap, pos) {
+                    expected += 1;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_select() {
+        // Set bits at 1, 3, 70, 130
+        let mut bitmap = vec![0_u8; 17];
+        for bit in [1, 3, 70, 130] {
+            arrow_buffer::bit_util::set_bit(&mut bitmap, bit);
+        }
+        let bitmap = LanceBuffer::Owned(bitmap);
+        assert_eq!(bitmap.select(0, 136), Some(1));
+        assert_eq!(bitmap.select(2, 136), Some(70));
+        assert_eq!(bitmap.select(3, 136), Some(130));
+        assert_eq!(bitmap.select(4, 136), None);
+        // Bits past len_bits are ignored
+        assert_eq!(bitmap.select(3, 130), None);
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+        let mut data = vec![0; 50];
+        rng.fill_bytes(&mut data);
+        let bitmap = LanceBuffer::Owned(data);
+        let len_bits = 397;
+        let set_bits = bitmap.set_bit_indices(len_bits);
+        for (n, pos) in set_bits.iter().enumerate() {
+            assert_eq!(bitmap.select(n, len_bits), Some(*pos as usize));
+        }
+        assert_eq!(bitmap.select(set_bits.len(), len_bits), None);
+    }
+
+    #[test]
+    fn test_concat_nullable() {
+        let chunks = vec![
+            (LanceBuffer::reinterpret_vec(vec![1_u32, 2, 3]), None, 3),
+            (
+                LanceBuffer::reinterpret_vec(vec![4_u32, 0]),
+                Some(LanceBuffer::Owned(vec![0b01])),
+                2,
+            ),
+            (LanceBuffer::reinterpret_vec(vec![6_u32]), None, 1),
+        ];
+        let (values, validity) = LanceBuffer::concat_nullable(&chunks, 4).unwrap();
+        assert_eq!(
+            values,
+            LanceBuffer::reinterpret_vec(vec![1_u32, 2, 3, 4, 0, 6])
+        );
+        assert_eq!(validity.unwrap().as_ref(), &[0b0010_1111]);
+
+        // All valid chunks produce no bitmap
+        let (values, validity) = LanceBuffer::concat_nullable(
+            &[
+                (LanceBuffer::Owned(vec![1]), None, 1),
+                (LanceBuffer::Owned(vec![2]), None, 1),
+            ],
+            1,
+        )
+        .unwrap();
+        assert_eq!(values.as_ref(), &[1, 2]);
+        assert!(validity.is_none());
+
+        // Values that don't match the value count
+        assert!(
+            LanceBuffer::concat_nullable(&[(LanceBuffer::Owned(vec![1, 2, 3]), None, 1)], 2)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_column_stats() {
+        let buffer = LanceBuffer::reinterpret_vec(vec![5_i32, 5, -3, -3, -3, 8, 5]);
+        let stats = buffer.column_stats::<i32>().unwrap();
+        assert_eq!(
+            stats,
+            super::ColumnStats {
+                num_values: 7,
+                num_runs: 4,
+                num_distinct: 3,
+                min: Some(-3),
+                max: Some(8),
+                is_constant: false,
+            }
+        );
+
+        let stats = LanceBuffer::reinterpret_vec(vec![2_u64; 10])
+            .column_stats::<u64>()
+            .unwrap();
+        assert!(stats.is_constant);
+        assert_eq!(stats.num_runs, 1);
+        assert_eq!(stats.num_distinct, 1);
+        assert_eq!((stats.min, stats.max), (Some(2), Some(2)));
+
+        // The distinct count is an estimate but it is the same every time
+        let many = LanceBuffer::reinterpret_vec((0..100_000_u64).collect::<Vec<_>>());
+        let stats = many.column_stats::<u64>().unwrap();
+        assert_eq!(stats, many.column_stats::<u64>().unwrap());
+        assert!(stats.num_distinct.abs_diff(100_000) < 5_000, "{:?}", stats);
+
+        let stats = LanceBuffer::empty().column_stats::<u32>().unwrap();
+        assert_eq!(stats.num_values, 0);
+        assert_eq!(stats.num_runs, 0);
+        assert!(!stats.is_constant);
+        assert_eq!(stats.min, None);
+
+        assert!(LanceBuffer::Owned(vec![0; 3])
+            .column_stats::<u16>()
+            .is_err());
+    }
+
+    #[test]
+    fn test_xor_parity() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+        for len in [0, 1, 7, 8, 9, 100, 1027] {
+            let mut data = vec![0; len];
+            rng.fill_bytes(&mut data);
+            let expected = data.iter().fold(0_u8, |parity, byte| parity ^ byte);
+            assert_eq!(LanceBuffer::Owned(data).xor_parity(), expected);
+        }
+
+        // A single byte error is detected
+        let buffer = LanceBuffer::Owned(vec![1, 2, 3, 4]);
+        let corrupted = LanceBuffer::Owned(vec![1, 2, 7, 4]);
+        assert_ne!(buffer.xor_parity(), corrupted.xor_parity());
+    }
+
+    #[test]
+    fn test_into_dictionary_array() {
+        use arrow_array::{cast::AsArray, types::Int32Type};
+
+        let indices = LanceBuffer::reinterpret_vec(vec![0_i32, 1, 0, 2, 1]);
+        let dictionary = LanceBuffer::reinterpret_vec(vec![100_i32, -5, 42]);
+        let array = LanceBuffer::into_dictionary_array::<Int32Type, Int32Type>(indices, dictionary)
+            .unwrap();
+        assert_eq!(array.len(), 5);
+        assert_eq!(array.keys().values().as_ref(), &[0, 1, 0, 2, 1]);
+        let values = array
+            .downcast_dict::<arrow_array::Int32Array>()
+            .unwrap()
+            .into_iter()
+            .map(|value| value.unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(values, vec![100, -5, 100, 42, -5]);
+        assert_eq!(array.values().as_primitive::<Int32Type>().len(), 3);
+
+        // Keys that are out of range for the dictionary
+        let indices = LanceBuffer::reinterpret_vec(vec![0_i32, 3]);
+        let dictionary = LanceBuffer::reinterpret_vec(vec![1_i32, 2]);
+        assert!(
+            LanceBuffer::into_dictionary_array::<Int32Type, Int32Type>(indices, dictionary)
+                .is_err()
+        );
+
+        // Keys that are not whole values
+        assert!(LanceBuffer::into_dictionary_array::<Int32Type, Int32Type>(
+            LanceBuffer::Owned(vec![0; 3]),
+            LanceBuffer::empty()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_deduplicate() {
+        let buffers = vec![
+            LanceBuffer::Owned(vec![1, 2, 3]),
+            LanceBuffer::Owned(vec![4]),
+            LanceBuffer::Borrowed(Buffer::from_vec(vec![1_u8, 2, 3])),
+            LanceBuffer::empty(),
+            LanceBuffer::Owned(vec![4]),
+            LanceBuffer::empty(),
+        ];
+        let (unique, remap) = super::deduplicate(buffers);
+        assert_eq!(
+            unique,
+            vec![
+                LanceBuffer::Owned(vec![1, 2, 3]),
+                LanceBuffer::Owned(vec![4]),
+                LanceBuffer::empty(),
+            ]
+        );
+        assert_eq!(remap, vec![0, 1, 0, 2, 1, 2]);
+
+        let (unique, remap) = super::deduplicate(Vec::new());
+        assert!(unique.is_empty());
+        assert!(remap.is_empty());
+    }
+
+    #[test]
+    fn test_from_boolean_array() {
+        let bools = (0..20).map(|i| i % 3 == 0).collect::<Vec<_>>();
+        let array = arrow_array::BooleanArray::from(bools.clone());
+
+        let (bitmap, num_bits) = LanceBuffer::from_boolean_array(&array);
+        assert_eq!(num_bits, 20);
+        assert_eq!(bitmap.len(), 3);
+
+        let sliced = array.slice(5, 11);
+        let (bitmap, num_bits) = LanceBuffer::from_boolean_array(&sliced);
+        assert_eq!(num_bits, 11);
+        assert_eq!(bitmap.len(), 2);
+        for (i, expected) in bools[5..16].iter().enumerate() {
+            assert_eq!(arrow_buffer::bit_util::get_bit(&bitmap, i), *expected);
+        }
+    }
+
+    #[test]
+    fn test_split_at_ratio() {
+        let buffer = LanceBuffer::Borrowed(Buffer::from_vec((0..10_u32).collect::<Vec<_>>()));
+        let (mut left, mut right) = buffer.split_at_ratio(0.5, 4);
+        assert!(matches!(left, LanceBuffer::Borrowed(_)));
+        assert_eq!(left.as_ptr(), buffer.as_ptr());
+        assert_eq!(
+            left.borrow_to_typed_slice::<u32>().as_ref(),
+            &[0, 1, 2, 3, 4]
+        );
+        assert_eq!(
+            right.borrow_to_typed_slice::<u32>().as_ref(),
+            &[5, 6, 7, 8, 9]
+        );
+
+        // Snaps to the nearest value boundary
+        let (left, right) = buffer.split_at_ratio(0.33, 4);
+        assert_eq!((left.len(), right.len()), (12, 28));
+
+        let (left, right) = buffer.split_at_ratio(0.0, 4);
+        assert_eq!((left.len(), right.len()), (0, 40));
+        let (left, right) = buffer.split_at_ratio(1.0, 4);
+        assert_eq!((left.len(), right.len()), (40, 0));
+    }
+
+    #[test]
+    #[should_panic(expected = "ratio between 0 and 1")]
+    fn test_split_at_ratio_out_of_range() {
+        LanceBuffer::Owned(vec![0; 8]).split_at_ratio(1.5, 4);
+    }
+
+    #[test]
+    fn test_share() {
+        let mut buffer = LanceBuffer::Owned(vec![1, 2, 3, 4, 5]);
+        let shared = buffer.share();
+        assert!(matches!(buffer, LanceBuffer::Shared(_)));
+        assert!(matches!(shared, LanceBuffer::Shared(_)));
+        let ptr = buffer.as_ptr();
+        assert_eq!(shared.as_ptr(), ptr);
+
+        // Further clones (with or without &mut) share the same memory
+        let clones = (0..3)
+            .map(|_| shared.try_clone().unwrap())
+            .chain(std::iter::once(buffer.share()))
+            .collect::<Vec<_>>();
+        for clone in &clones {
+            assert_eq!(clone.as_ptr(), ptr);
+            assert_eq!(clone, &LanceBuffer::Owned(vec![1, 2, 3, 4, 5]));
+        }
+
+        // Converting or slicing a shared buffer does not copy
+        let slice = shared.slice_with_length(1, 3);
+        assert_eq!(slice.as_ptr(), ptr.wrapping_add(1));
+        assert_eq!(slice.as_ref(), &[2, 3, 4]);
+        assert_eq!(shared.try_clone().unwrap().into_buffer().as_ptr(), ptr);
+        assert_eq!(shared.try_clone().unwrap().into_bytes().as_ptr(), ptr);
+        assert_eq!(
+            clones
+                .into_iter()
+                .next()
+                .unwrap()
+                .into_iter()
+                .collect::<Vec<_>>(),
+            vec![1, 2, 3, 4, 5]
+        );
+        assert!(format!("{:?}", shared).starts_with("LanceBuffer::Shared"));
+
+        // Mutating a shared buffer copies it into owned mode and leaves the other clones alone
+        let mut mutated = shared.try_clone().unwrap();
+        mutated.push_value(6_u8);
+        assert!(matches!(mutated, LanceBuffer::Owned(_)));
+        assert_eq!(mutated.as_ref(), &[1, 2, 3, 4, 5, 6]);
+        assert_eq!(shared.as_ref(), &[1, 2, 3, 4, 5]);
+
+        // Borrowed buffers stay borrowed
+        let mut borrowed = LanceBuffer::Borrowed(Buffer::from_vec(vec![1_u8, 2]));
+        let clone = borrowed.share();
+        assert!(matches!(clone, LanceBuffer::Borrowed(_)));
+        assert_eq!(clone.as_ptr(), borrowed.as_ptr());
+    }
+
+    #[test]
+    fn test_compact() {
+        let backing = Arc::<[u8]>::from(vec![7_u8; 1024 * 1024]);
+        let big = LanceBuffer::reinterpret_slice(backing.clone());
+        let mut slice = big.slice_with_length(100, 16);
+        drop(big);
+        assert_eq!(Arc::strong_count(&backing), 2);
+
+        slice.compact();
+        assert!(matches!(slice, LanceBuffer::Owned(_)));
+        assert_eq!(slice.as_ref(), &[7; 16]);
+        // The large allocation is no longer kept alive by the slice
+        assert_eq!(Arc::strong_count(&backing), 1);
+
+        // Buffers that use most of their allocation are left alone
+        let mut buffer = LanceBuffer::Borrowed(Buffer::from_vec(vec![1_u8; 100]));
+        let ptr = buffer.as_ptr();
+        buffer.compact();
+        assert!(matches!(buffer, LanceBuffer::Borrowed(_)));
+        assert_eq!(buffer.as_ptr(), ptr);
+
+        // The size of the allocation behind a Bytes is unknown so buffers created with
+        // from_bytes are left alone
+        let big = bytes::Bytes::from(vec![7_u8; 1024 * 1024]);
+        let mut from_bytes = LanceBuffer::from_bytes(big.slice(100..116), 1);
+        drop(big);
+        let ptr = from_bytes.as_ptr();
+        from_bytes.compact();
+        assert!(matches!(from_bytes, LanceBuffer::Borrowed(_)));
+        assert_eq!(from_bytes.as_ptr(), ptr);
+        assert_eq!(from_bytes.as_ref(), &[7; 16]);
+    }
+
+    #[test]
+    fn test_bitwise_binary_ops() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+        // Lengths that are and are not a multiple of the word size
+        for len in [0, 3, 8, 21] {
+            let mut left = vec![0; len];
+            let mut right = vec![0; len];
+            rng.fill_bytes(&mut left);
+            rng.fill_bytes(&mut right);
+            let expected = |op: fn(u8, u8) -> u8| {
+                left.iter()
+                    .zip(&right)
+                    .map(|(l, r)| op(*l, *r))
+                    .collect::<Vec<_>>()
+            };
+            let lhs = LanceBuffer::Owned(left.clone());
+            let rhs = LanceBuffer::Borrowed(Buffer::from_vec(right.clone()));
+
+            let and = lhs.bit_and(&rhs).unwrap();
+            assert!(matches!(and, LanceBuffer::Owned(_)));
+            assert_eq!(and.as_ref(), expected(|l, r| l & r).as_slice());
+            assert_eq!(
+                lhs.bit_or(&rhs).unwrap().as_ref(),
+                expected(|l, r| l | r).as_slice()
+            );
+            assert_eq!(
+                lhs.bit_xor(&rhs).unwrap().as_ref(),
+                expected(|l, r| l ^ r).as_slice()
+            );
+        }
+
+        let lhs = LanceBuffer::Owned(vec![0; 4]);
+        let rhs = LanceBuffer::Owned(vec![0; 5]);
+        assert!(matches!(
+            lhs.bit_and(&rhs),
+            Err(lance_core::Error::InvalidInput { .. })
+        ));
+        assert!(lhs.bit_or(&rhs).is_err());
+        assert!(lhs.bit_xor(&LanceBuffer::empty()).is_err());
+    }
+
+    #[cfg(all(feature = "checksum", feature = "zstd"))]
+    #[test]
+    fn test_verify_and_decompress_zstd() {
+        let data = (0..10_000_u32)
+            .flat_map(|i| (i % 100).to_le_bytes())
+            .collect::<Vec<_>>();
+        let compressed = zstd::bulk::compress(&data, 3).unwrap();
+        let crc = crc32c::crc32c(&compressed);
+        let buffer = LanceBuffer::Owned(compressed.clone());
+
+        let decompressed = buffer.verify_and_decompress_zstd(crc, data.len()).unwrap();
+        assert_eq!(decompressed.as_ref(), data.as_slice());
+
+        let err = buffer
+            .verify_and_decompress_zstd(crc ^ 1, data.len())
+            .unwrap_err();
+        assert!(err.to_string().contains("checksum mismatch"), "{}", err);
+
+        // Data that was corrupted before the checksum was computed
+        let mut corrupt = compressed;
+        corrupt.truncate(corrupt.len() / 2);
+        let corrupt_crc = crc32c::crc32c(&corrupt);
+        let err = LanceBuffer::Owned(corrupt)
+            .verify_and_decompress_zstd(corrupt_crc, data.len())
+            .unwrap_err();
+        assert!(err.to_string().contains("checksum verified"), "{}", err);
+
+        // A checksum-valid block with the wrong decompressed length
+        let err = buffer
+            .verify_and_decompress_zstd(crc, data.len() + 1)
+            .unwrap_err();
+        assert!(err.to_string().contains("checksum verified"), "{}", err);
+
+        // A huge claimed length is rejected without allocating it
+        let err = buffer
+            .verify_and_decompress_zstd(crc, usize::MAX)
+            .unwrap_err();
+        assert!(err.to_string().contains("checksum verified"), "{}", err);
+    }
+
+    #[test]
+    fn test_bit_not() {
+        let buffer = LanceBuffer::Owned((0..19).collect());
+        let inverted = buffer.bit_not();
+        assert!(matches!(inverted, LanceBuffer::Owned(_)));
+        assert_eq!(
+            inverted.as_ref(),
+            (0..19_u8).map(|byte| !byte).collect::<Vec<_>>().as_slice()
+        );
+        assert!(LanceBuffer::empty().bit_not().is_empty());
+
+        let bitmap = LanceBuffer::Owned(vec![0b0101_0101, 0b0000_0000, 0b1111_0000]);
+        // Not byte-aligned, the trailing bits of the last partial byte are masked
+        assert_eq!(
+            bitmap.bit_not_with_len(11).as_ref(),
+            &[0b1010_1010, 0b0000_0111, 0b0000_0000]
+        );
+        // Byte-aligned
+        assert_eq!(
+            bitmap.bit_not_with_len(8).as_ref(),
+            &[0b1010_1010, 0b0000_0000, 0b0000_0000]
+        );
+        assert_eq!(
+            bitmap.bit_not_with_len(24).as_ref(),
+            &[0b1010_1010, 0b1111_1111, 0b0000_1111]
+        );
+        assert_eq!(bitmap.bit_not_with_len(0).as_ref(), &[0, 0, 0]);
+    }
+
+    #[test]
+    fn test_offsets_and_lengths() {
+        let offsets = LanceBuffer::reinterpret_vec(vec![0_i32, 3, 3, 10, 12]);
+        let mut lengths = offsets.offsets_to_lengths::<i32>().unwrap();
+        assert_eq!(
+            lengths.borrow_to_typed_slice::<i32>().as_ref(),
+            &[3, 0, 7, 2]
+        );
+        let round_trip = lengths.lengths_to_offsets::<i32>().unwrap();
+        assert_eq!(round_trip, offsets);
+
+        // A single offset has no lengths and no lengths produce a single offset
+        let single = LanceBuffer::reinterpret_vec(vec![0_i32]);
+        let lengths = single.offsets_to_lengths::<i32>().unwrap();
+        assert!(lengths.is_empty());
+        assert_eq!(lengths.lengths_to_offsets::<i32>().unwrap(), single);
+
+        let decreasing = LanceBuffer::reinterpret_vec(vec![0_i32, 5, 4]);
+        assert!(decreasing.offsets_to_lengths::<i32>().is_err());
+        assert!(LanceBuffer::Owned(vec![0; 6])
+            .offsets_to_lengths::<i32>()
+            .is_err());
+        let overflowing = LanceBuffer::reinterpret_vec(vec![i32::MAX, 1]);
+        assert!(overflowing.lengths_to_offsets::<i32>().is_err());
+    }
+
+    #[test]
+    fn test_count_ones() {
+        let naive = |data: &[u8], offset: usize, len: usize| {
+            (offset..offset + len)
+                .filter(|bit| arrow_buffer::bit_util::get_bit(data, *bit))
+                .count() as u64
+        };
+        let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+        for len in [0, 1, 7, 8, 9, 63, 100] {
+            let mut data = vec![0; len];
+            rng.fill_bytes(&mut data);
+            let buffer = LanceBuffer::Owned(data.clone());
+            assert_eq!(buffer.count_ones(), naive(&data, 0, len * 8));
+            // A slice starting partway into an allocation has unaligned leading bytes
+            if len > 3 {
+                let sliced = LanceBuffer::Borrowed(Buffer::from_vec(data.clone()).slice(3));
+                assert_eq!(sliced.count_ones(), naive(&data[3..], 0, (len - 3) * 8));
+            }
+            for _ in 0..20 {
+                let offset = rng.gen_range(0..=len * 8);
+                let num_bits = rng.gen_range(0..=len * 8 - offset);
+                assert_eq!(
+                    buffer.count_ones_range(offset, num_bits),
+                    naive(&data, offset, num_bits)
+                );
+            }
+        }
+
+        // Partial leading and trailing bytes are masked
+        let buffer = LanceBuffer::Owned(vec![0xFF, 0xFF]);
+        assert_eq!(buffer.count_ones_range(3, 2), 2);
+        assert_eq!(buffer.count_ones_range(5, 6), 6);
+    }
+
+    #[test]
+    fn test_reinterpret_width() {
+        let mut buffer = LanceBuffer::reinterpret_vec(vec![1_u32, 2, 3, 4]);
+        let ptr = buffer.as_ptr();
+        let values = buffer.reinterpret_width::<u32>().unwrap();
+        assert_eq!(values.as_ref(), &[1, 2, 3, 4]);
+        assert_eq!(values.inner().as_ptr(), ptr);
+        // Widths that evenly divide the buffer are ok too
+        assert_eq!(buffer.reinterpret_width::<u8>().unwrap().len(), 16);
+
+        // Misaligned (sliced by one byte)
+        let mut misaligned = buffer.slice_with_length(1, 8);
+        assert!(misaligned.reinterpret_width::<u32>().is_err());
+        // Not evenly divisible
+        let mut odd = buffer.slice_with_length(0, 6);
+        assert!(odd.reinterpret_width::<u32>().is_err());
+    }
+
+    #[test]
+    fn test_bit_slice_be() {
+        let mut buf = LanceBuffer::Owned(vec![0x0F, 0x0B]);
+
+        // These mirror test_bit_slice_le but bit 0 is the most significant bit of byte 0
+        assert_eq!(buf.bit_slice_be_with_length(0, 4).as_ref(), &[0x0F]);
+        assert_eq!(buf.bit_slice_be_with_length(4, 4).as_ref(), &[0xF0]);
+        assert_eq!(buf.bit_slice_be_with_length(3, 8).as_ref(), &[0x78]);
+        assert_eq!(buf.bit_slice_be_with_length(0, 8).as_ref(), &[0x0F]);
+        assert_eq!(buf.bit_slice_be_with_length(4, 8).as_ref(), &[0xF0]);
+        assert_eq!(buf.bit_slice_be_with_length(4, 12).as_ref(), &[0xF0, 0xB0]);
+        // Unaligned, spanning multiple bytes, with a partial final byte
+        assert_eq!(buf.bit_slice_be_with_length(5, 10).as_ref(), &[0xE1, 0x40]);
+        assert_eq!(buf.bit_slice_be_with_length(15, 1).as_ref(), &[0x80]);
+    }
+
+    #[test]
+    fn test_value_histogram() {
+        let buffer = LanceBuffer::Owned(vec![3, 1, 3, 3, 7, 1, 0]);
+        let histogram = buffer.value_histogram::<u8>().unwrap();
+        assert_eq!(histogram.len(), 4);
+        assert_eq!(histogram[&3], 3);
+        assert_eq!(histogram[&1], 2);
+        assert_eq!(histogram[&7], 1);
+        assert_eq!(histogram[&0], 1);
+
+        assert!(LanceBuffer::empty()
+            .value_histogram::<u32>()
+            .unwrap()
+            .is_empty());
+        assert!(buffer.value_histogram::<u16>().is_err());
+    }
+
+    #[test]
+    fn test_concat_into_one_aligned() {
+        let buffers = || {
+            vec![
+                LanceBuffer::Owned(vec![1, 2, 3]),
+                LanceBuffer::empty(),
+                LanceBuffer::Borrowed(Buffer::from_vec(vec![4_u8, 5])),
+            ]
+        };
+        for align in [1, 8, 64, 4096] {
+            let concatenated = LanceBuffer::concat_into_one_aligned(buffers(), align);
+            assert!(matches!(concatenated, LanceBuffer::Borrowed(_)));
+            assert_eq!(concatenated.as_ptr().align_offset(align), 0);
+            assert_eq!(concatenated.as_ref(), &[1, 2, 3, 4, 5]);
+        }
+        assert_eq!(
+            LanceBuffer::concat_into_one_aligned(buffers(), 4096),
+            LanceBuffer::concat_into_one(buffers())
+        );
+        assert_eq!(
+            LanceBuffer::concat(&buffers()),
+            LanceBuffer::concat_into_one(buffers())
+        );
+
+        let empty = LanceBuffer::concat_into_one_aligned(vec![LanceBuffer::empty()], 128);
+        assert!(empty.is_empty());
+        assert_eq!(empty.as_ptr().align_offset(128), 0);
+        assert!(
+            LanceBuffer::concat_into_one(vec![LanceBuffer::empty(), LanceBuffer::empty()])
+                .is_empty()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "power of two")]
+    fn test_concat_into_one_aligned_bad_alignment() {
+        LanceBuffer::concat_into_one_aligned(vec![LanceBuffer::Owned(vec![1])], 3);
+    }
+
+    #[test]
+    fn test_zero_null_values() {
+        let mut values =
+            LanceBuffer::Borrowed(Buffer::from_vec(vec![1_u32, 2, 3, 4, 5, 6, 7, 8, 9]));
+        // Values 2 and 7 are null
+        let validity = LanceBuffer::Owned(vec![0b0111_1011, 0b0000_0001]);
+        values.zero_null_values(&validity, 4);
+        assert!(matches!(values, LanceBuffer::Owned(_)));
+        assert_eq!(
+            values,
+            LanceBuffer::reinterpret_vec(vec![1_u32, 2, 0, 4, 5, 6, 7, 0, 9])
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "too short")]
+    fn test_zero_null_values_short_validity() {
+        let mut values = LanceBuffer::Owned(vec![1; 9]);
+        values.zero_null_values(&LanceBuffer::Owned(vec![0xFF]), 1);
+    }
+
+    #[test]
+    fn test_with_alignment() {
+        let data = (0..100_u8).collect::<Vec<_>>();
+        for align in [1, 2, 8, 64, 4096] {
+            let mut buffer = LanceBuffer::with_alignment(&data, align);
+            assert_eq!(buffer.as_ptr().align_offset(align), 0);
+            assert!(buffer.is_aligned_to(align));
+            assert_eq!(buffer.as_ref(), data.as_slice());
+
+            let empty = LanceBuffer::with_alignment(&[], align);
+            assert!(empty.is_empty());
+            assert_eq!(empty.as_ptr().align_offset(align), 0);
+
+            if align >= 4 {
+                // Reinterpretation is zero-copy
+                let ptr = buffer.as_ptr();
+                let values = buffer.reinterpret_width::<u32>().unwrap();
+                assert_eq!(values.inner().as_ptr(), ptr);
+            }
+        }
+
+        let buffer = LanceBuffer::with_alignment(&data, 64);
+        assert!(!buffer.slice_with_length(1, 8).is_aligned_to(2));
+        assert!(buffer.slice_with_length(8, 8).is_aligned_to(8));
+    }
+
+    #[test]
+    #[should_panic(expected = "power of two")]
+    fn test_with_alignment_not_power_of_two() {
+        LanceBuffer::with_alignment(&[1, 2, 3], 12);
+    }
+
+    #[test]
+    #[should_panic(expected = "power of two but was 0")]
+    fn test_is_aligned_to_zero() {
+        LanceBuffer::Owned(vec![1, 2, 3]).is_aligned_to(0);
+    }
+
+    #[cfg(feature = "checksum")]
+    #[test]
+    fn test_concat_with_component_crcs() {
+        let parts = [vec![1_u8, 2, 3], vec![], vec![4, 5, 6, 7, 8]];
+        let buffers = parts
+            .iter()
+            .map(|part| LanceBuffer::Owned(part.clone()))
+            .collect();
+        let (combined, crcs) = LanceBuffer::concat_with_component_crcs(buffers);
+        assert_eq!(combined.as_ref(), &[1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(
+            crcs,
+            parts
+                .iter()
+                .map(|part| crc32c::crc32c(part))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_try_borrow_to_typed_slice() {
+        let mut buffer = LanceBuffer::Owned(vec![1, 2, 3]);
+        assert!(matches!(
+            buffer.try_borrow_to_typed_slice::<u16>(),
+            Err(lance_core::Error::InvalidInput { .. })
+        ));
+
+        let mut buffer = LanceBuffer::reinterpret_vec(vec![1_u16, 2, 3]);
+        let ptr = buffer.as_ptr();
+        let values = buffer.try_borrow_to_typed_slice::<u16>().unwrap();
+        assert_eq!(values.as_ref(), &[1, 2, 3]);
+        // Aligned buffers are not copied
+        assert_eq!(values.inner().as_ptr(), ptr);
+
+        // Misaligned buffers are copied
+        let mut misaligned =
+            LanceBuffer::Borrowed(Buffer::from_vec(vec![0_u8, 1, 0, 2, 0])).slice_with_length(1, 4);
+        let values = misaligned.try_borrow_to_typed_slice::<u16>().unwrap();
+        assert_eq!(values.as_ref(), &[1, 2]);
+        assert_ne!(values.inner().as_ptr(), misaligned.as_ptr());
+    }
+
+    #[test]
+    fn test_binary_search_value() {
+        let buffer = LanceBuffer::reinterpret_vec(vec![-10_i64, -3, 0, 4, 4, 9, 100]);
+        assert_eq!(buffer.binary_search_value(-3_i64).unwrap(), Ok(1));
+        assert_eq!(buffer.binary_search_value(100_i64).unwrap(), Ok(6));
+        assert!(matches!(
+            buffer.binary_search_value(4_i64).unwrap(),
+            Ok(3) | Ok(4)
+        ));
+        assert_eq!(buffer.binary_search_value(5_i64).unwrap(), Err(5));
+        assert_eq!(buffer.binary_search_value(-11_i64).unwrap(), Err(0));
+        assert_eq!(buffer.binary_search_value(101_i64).unwrap(), Err(7));
+
+        assert_eq!(
+            LanceBuffer::empty().binary_search_value(7_u32).unwrap(),
+            Err(0)
+        );
+        assert!(LanceBuffer::Owned(vec![0; 3])
+            .binary_search_value(0_u16)
+            .is_err());
+    }
+
+    #[test]
+    fn test_into_bytes_shared() {
+        let bytes = bytes::Bytes::from(vec![1_u8, 2, 3, 4, 5, 6, 7, 8]);
+        let buffer = LanceBuffer::from_bytes(bytes, 1);
+        let clone = buffer.try_clone().unwrap();
+        assert_eq!(buffer.into_bytes().as_ref(), &[1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(clone.into_bytes().as_ref(), &[1, 2, 3, 4, 5, 6, 7, 8]);
+
+        // A slice of a shared Vec allocation
+        let mut buffer = LanceBuffer::Owned(vec![1, 2, 3, 4]);
+        let slice = buffer.borrow_and_clone().slice_with_length(1, 2);
+        assert_eq!(slice.into_bytes().as_ref(), &[2, 3]);
+
+        // A buffer that is the sole owner of its allocation is not copied
+        let buffer = LanceBuffer::Borrowed(Buffer::from_vec(vec![9_u8, 8, 7]));
+        let ptr = buffer.as_ptr();
+        let bytes = buffer.into_bytes();
+        assert_eq!(bytes.as_ptr(), ptr);
+        assert_eq!(bytes.as_ref(), &[9, 8, 7]);
+    }
+
+    #[test]
+    fn test_copy_within() {
+        let mut buffer = LanceBuffer::Borrowed(Buffer::from_vec((0..10_u8).collect::<Vec<_>>()));
+        // Shift the middle forward, overlapping the source
+        buffer.copy_within(2..6, 4);
+        assert!(matches!(buffer, LanceBuffer::Owned(_)));
+        assert_eq!(buffer.as_ref(), &[0, 1, 2, 3, 2, 3, 4, 5, 8, 9]);
+
+        // And backward
+        buffer.copy_within(4..8, 0);
+        assert_eq!(buffer.as_ref(), &[2, 3, 4, 5, 2, 3, 4, 5, 8, 9]);
+
+        // An empty range is a no-op
+        buffer.copy_within(3..3, 10);
+        assert_eq!(buffer.as_ref(), &[2, 3, 4, 5, 2, 3, 4, 5, 8, 9]);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn test_copy_within_out_of_bounds() {
+        LanceBuffer::Owned(vec![0; 10]).copy_within(2..6, 7);
+    }
+
+    #[test]
+    fn test_unzip_into_many() {
+        let num_values = 100;
+        let columns = [(1_usize, 8_u64), (4, 32), (2, 16), (8, 64)]
+            .iter()
+            .map(|(width, bits)| {
+                let data = (0..num_values * width)
+                    .map(|i| (i * 7 + width) as u8)
+                    .collect::<Vec<_>>();
+                (data, *bits)
+            })
+            .collect::<Vec<_>>();
+        let widths = columns.iter().map(|(_, bits)| *bits).collect::<Vec<_>>();
+        let zipped = LanceBuffer::zip_into_one(
+            columns
+                .iter()
+                .map(|(data, bits)| (LanceBuffer::Owned(data.clone()), *bits))
+                .collect(),
+            num_values as u64,
+        )
+        .unwrap();
+
+        let unzipped = LanceBuffer::unzip_into_many(zipped, &widths, num_values as u64).unwrap();
+        assert_eq!(unzipped.len(), columns.len());
+        for (buffer, (data, _)) in unzipped.iter().zip(&columns) {
+            assert!(matches!(buffer, LanceBuffer::Owned(_)));
+            assert_eq!(buffer.as_ref(), data.as_slice());
+        }
+
+        // The length doesn't match the widths
+        let buffer = LanceBuffer::Owned(vec![0; 10]);
+        assert!(LanceBuffer::unzip_into_many(buffer, &[8, 16], 4).is_err());
+        // Partial byte widths that don't match the length
+        let buffer = LanceBuffer::Owned(vec![0; 10]);
+        assert!(LanceBuffer::unzip_into_many(buffer, &[4, 4], 11).is_err());
+    }
+
+    #[test]
+    fn test_then() {
+        let parts = || {
+            vec![
+                LanceBuffer::Owned(vec![1, 2, 3]),
+                LanceBuffer::Borrowed(Buffer::from_vec(vec![4_u8, 5])),
+                LanceBuffer::Owned(vec![6]),
+            ]
+        };
+        let mut chained = parts().into_iter();
+        let first = chained.next().unwrap();
+        let chained = first
+            .then(chained.next().unwrap())
+            .then(chained.next().unwrap());
+        assert_eq!(chained, LanceBuffer::concat_into_one(parts()));
+
+        // Owned buffers are appended to in place
+        let mut data = Vec::with_capacity(16);
+        data.extend_from_slice(&[1, 2]);
+        let ptr = data.as_ptr();
+        let chained = LanceBuffer::Owned(data).then(LanceBuffer::Owned(vec![3, 4]));
+        assert_eq!(chained.as_ptr(), ptr);
+        assert_eq!(chained.as_ref(), &[1, 2, 3, 4]);
+
+        // Empty buffers are skipped without copying
+        let borrowed = LanceBuffer::Borrowed(Buffer::from_vec(vec![7_u8, 8]));
+        let ptr = borrowed.as_ptr();
+        let chained = LanceBuffer::empty()
+            .then(borrowed)
+            .then(LanceBuffer::empty());
+        assert_eq!(chained.as_ptr(), ptr);
+    }
+
+    #[test]
+    fn test_zip_sub_byte_widths() {
+        // A 3-bit and a 5-bit column produce one byte per value
+        let threes = LanceBuffer::Owned(vec![0b1100_0101, 0b0000_0101]); // 5, 0, 7, 2
+        let fives = LanceBuffer::Owned(vec![0b0011_1111, 0b0010_1110, 0]); // 31, 17, 11, 0
+        let zipped = LanceBuffer::zip_into_one(vec![(threes, 3), (fives, 5)], 4).unwrap();
+        assert_eq!(zipped.as_ref(), &[5 | (31 << 3), 17 << 3, 7 | (11 << 3), 2]);
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+        let num_values = 37;
+        for widths in [vec![1_u64, 3, 8], vec![8, 1], vec![3, 64, 5, 77]] {
+            let columns = widths
+                .iter()
+                .map(|bits_per_value| {
+                    let num_bits = *bits_per_value as usize * num_values;
+                    let mut data = vec![0; num_bits.div_ceil(8)];
+                    rng.fill_bytes(&mut data);
+                    // Clear the padding bits so the columns are clean bitmaps
+                    if num_bits % 8 != 0 {
+                        *data.last_mut().unwrap() &= (1 << (num_bits % 8)) - 1;
+                    }
+                    data
+                })
+                .collect::<Vec<_>>();
+            let zipped = LanceBuffer::zip_into_one(
+                columns
+                    .iter()
+                    .zip(&widths)
+                    .map(|(data, bits)| (LanceBuffer::Owned(data.clone()), *bits))
+                    .collect(),
+                num_values as u64,
+            )
+            .unwrap();
+            let total_bits = widths.iter().sum::<u64>() as usize * num_values;
+            assert_eq!(zipped.len(), total_bits.div_ceil(8));
+
+            let unzipped =
+                LanceBuffer::unzip_into_many(zipped, &widths, num_values as u64).unwrap();
+            for (buffer, data) in unzipped.iter().zip(&columns) {
+                assert_eq!(buffer.as_ref(), data.as_slice());
+            }
+        }
+
+        // Inputs that are too short for the number of values
+        let short = LanceBuffer::Owned(vec![0xFF]);
+        assert!(LanceBuffer::zip_into_one(vec![(short, 3)], 3).is_err());
+    }
+
+    #[test]
+    fn test_reservoir_sample() {
+        let buffer = LanceBuffer::reinterpret_vec((0..10_000_u32).collect::<Vec<_>>());
+        let mut sample = buffer.reservoir_sample(100, 4, 42);
+        assert_eq!(sample.len(), 400);
+        assert_eq!(sample, buffer.reservoir_sample(100, 4, 42));
+        assert_ne!(sample, buffer.reservoir_sample(100, 4, 43));
+        // Every sampled value is distinct and comes from the input
+        let values = sample.borrow_to_typed_slice::<u32>();
+        let distinct = values.iter().collect::<std::collections::HashSet<_>>();
+        assert_eq!(distinct.len(), 100);
+        assert!(values.iter().all(|value| *value < 10_000));
+
+        // The sample size is capped by the number of values
+        let small = LanceBuffer::Owned(vec![1, 2, 3]);
+        assert_eq!(small.reservoir_sample(10, 1, 7).as_ref(), &[1, 2, 3]);
+        assert!(small.reservoir_sample(0, 1, 7).is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+        let mut large = vec![0; 5 * 1024];
+        rng.fill_bytes(&mut large);
+
+        for data in [Vec::new(), vec![1, 2, 3], large] {
+            let buffer = LanceBuffer::Borrowed(Buffer::from_vec(data.clone()));
+
+            let encoded = bincode::serialize(&buffer).unwrap();
+            let decoded: LanceBuffer = bincode::deserialize(&encoded).unwrap();
+            assert!(matches!(decoded, LanceBuffer::Owned(_)));
+            assert_eq!(decoded.as_ref(), data.as_slice());
+
+            let json = serde_json::to_string(&buffer).unwrap();
+            let decoded: LanceBuffer = serde_json::from_str(&json).unwrap();
+            assert!(matches!(decoded, LanceBuffer::Owned(_)));
+            assert_eq!(decoded.as_ref(), data.as_slice());
+        }
+
+        // Human readable formats use base64
+        let json = serde_json::to_string(&LanceBuffer::Owned(vec![1, 2, 3])).unwrap();
+        assert_eq!(json, "\"AQID\"");
+        // A JSON array of bytes is also accepted
+        let decoded: LanceBuffer = serde_json::from_str("[4, 5, 6]").unwrap();
+        assert_eq!(decoded.as_ref(), &[4, 5, 6]);
+        assert!(serde_json::from_str::<LanceBuffer>("\"not base64!\"").is_err());
+    }
+
+    #[test]
+    fn test_into_arc_typed_slice() {
+        let buffer = LanceBuffer::reinterpret_vec((0..1000_u32).collect::<Vec<_>>());
+        let values = buffer.into_arc_typed_slice::<u32>().unwrap();
+        assert_eq!(values.len(), 1000);
+        assert!(values.iter().copied().eq(0..1000));
+
+        let handles = (0..2)
+            .map(|_| {
+                let values = values.clone();
+                std::thread::spawn(move || values.iter().map(|v| *v as u64).sum::<u64>())
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), 999 * 1000 / 2);
+        }
+
+        // Misaligned buffers are read value by value
+        let data = (0..1000_u32).flat_map(u32::to_ne_bytes).collect::<Vec<_>>();
+        let misaligned =
+            LanceBuffer::Borrowed(Buffer::from_vec([&[0][..], &data].concat()).slice(1));
+        assert_ne!(misaligned.as_ptr().align_offset(4), 0);
+        let values = misaligned.into_arc_typed_slice::<u32>().unwrap();
+        assert!(values.iter().copied().eq(0..1000));
+        assert!(LanceBuffer::Owned(vec![0; 3])
+            .into_arc_typed_slice::<u32>()
+            .is_err());
+    }
+
+    #[test]
+    fn test_from_reader() {
+        let mut reader = std::io::Cursor::new((0..10_u8).collect::<Vec<_>>());
+        let first = LanceBuffer::from_reader(&mut reader, 4).unwrap();
+        assert!(matches!(first, LanceBuffer::Owned(_)));
+        assert_eq!(first.as_ref(), &[0, 1, 2, 3]);
+        let second = LanceBuffer::from_reader(&mut reader, 6).unwrap();
+        assert_eq!(second.as_ref(), &[4, 5, 6, 7, 8, 9]);
+        assert!(LanceBuffer::from_reader(&mut reader, 0).unwrap().is_empty());
+
+        let mut short = std::io::Cursor::new(vec![1_u8, 2, 3]);
+        let err = LanceBuffer::from_reader(&mut short, 4).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_strip_prefix_suffix() {
+        let buffer = LanceBuffer::Borrowed(Buffer::from_vec(b"LNCEbodyEND".to_vec()));
+
+        let body = buffer.strip_prefix(b"LNCE").unwrap();
+        assert_eq!(body.as_ref(), b"bodyEND");
+        assert_eq!(body.as_ptr(), buffer.as_ptr().wrapping_add(4));
+        let body = body.strip_suffix(b"END").unwrap();
+        assert_eq!(body.as_ref(), b"body");
+        assert_eq!(body.as_ptr(), buffer.as_ptr().wrapping_add(4));
+
+        assert!(buffer.strip_prefix(b"XYZ").is_none());
+        assert!(buffer.strip_suffix(b"LNCE").is_none());
+        assert!(buffer.strip_prefix(b"LNCEbodyEND!").is_none());
+        assert!(buffer.strip_prefix(b"LNCEbodyEND").unwrap().is_empty());
+        assert_eq!(buffer.strip_suffix(b"").unwrap(), buffer);
+
+        let owned = LanceBuffer::Owned(b"LNCEbody".to_vec());
+        assert_eq!(owned.strip_prefix(b"LNCE").unwrap().as_ref(), b"body");
+    }
+
+    #[test]
+    fn test_write_to() {
+        let buffers = [
+            LanceBuffer::Owned(vec![1, 2, 3]),
+            LanceBuffer::empty(),
+            LanceBuffer::Borrowed(Buffer::from_vec(vec![4_u8, 5])),
+            LanceBuffer::Owned(vec![6]).share(),
+        ];
 
-        let mut expected = Vec::with_capacity(12);
-        expected.extend_from_slice(&1_u32.to_ne_bytes());
-        expected.extend_from_slice(&2_u32.to_ne_bytes());
-        expected.extend_from_slice(&3_u32.to_ne_bytes());
-        let expected = LanceBuffer::Owned(expected);
+        let mut out = Vec::new();
+        for buffer in &buffers {
+            buffer.write_all_to(&mut out).unwrap();
+        }
+        assert_eq!(out, vec![1, 2, 3, 4, 5, 6]);
 
-        assert_eq!(expected, buf);
-        assert_eq!(buf.borrow_to_typed_slice::<u32>().as_ref(), vec![1, 2, 3]);
+        let mut out = Vec::new();
+        LanceBuffer::write_vectored_to(&buffers.iter().collect::<Vec<_>>(), &mut out).unwrap();
+        let expected =
+            LanceBuffer::concat_into_one(buffers.iter().map(|b| b.deep_copy()).collect());
+        assert_eq!(out.as_slice(), expected.as_ref());
+
+        // A writer that accepts at most two bytes per call still receives everything
+        struct Trickle(Vec<u8>);
+        impl std::io::Write for Trickle {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                let n = buf.len().min(2);
+                self.0.extend_from_slice(&buf[..n]);
+                Ok(n)
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+        let mut trickle = Trickle(Vec::new());
+        LanceBuffer::write_vectored_to(&buffers.iter().collect::<Vec<_>>(), &mut trickle).unwrap();
+        assert_eq!(trickle.0.as_slice(), expected.as_ref());
+
+        let mut full = [0_u8; 4];
+        let err = LanceBuffer::write_vectored_to(
+            &buffers.iter().collect::<Vec<_>>(),
+            &mut full.as_mut_slice(),
+        )
+        .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::WriteZero);
     }
 
     #[test]
-    fn test_concat() {
-        let buf1 = LanceBuffer::Owned(vec![1_u8, 2, 3]);
-        let buf2 = LanceBuffer::Owned(vec![4_u8, 5, 6]);
-        let buf3 = LanceBuffer::Owned(vec![7_u8, 8, 9]);
+    fn test_morton_nway() {
+        let x = LanceBuffer::reinterpret_vec(vec![0_u16, 1, 0, 0, 0x1FFF, 1234]);
+        let y = LanceBuffer::reinterpret_vec(vec![0_u16, 0, 1, 0, 0, 4321]);
+        let z = LanceBuffer::reinterpret_vec(vec![0_u16, 0, 0, 1, 0x1FFF, 8000]);
+        let dims = [x, y, z];
 
-        let expected = LanceBuffer::Owned(vec![1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        let codes = LanceBuffer::morton_encode_nway(&dims, 13).unwrap();
+        let codes_view = codes.typed_values::<u64>().unwrap().into_owned();
+        assert_eq!(&codes_view[..4], &[0, 1, 2, 4]);
+        assert_eq!(codes_view[4], 0o1111111111111 | (0o1111111111111 << 2));
+
+        let decoded = codes.morton_decode_nway(3, 13).unwrap();
+        assert_eq!(decoded, dims);
+
+        // A value too wide for bits_per_dim
+        assert!(LanceBuffer::morton_encode_nway(&dims, 12).is_err());
+        // Too many bits for a u64
+        let wide = [
+            LanceBuffer::reinterpret_vec(vec![0_u16]),
+            LanceBuffer::reinterpret_vec(vec![0_u16]),
+            LanceBuffer::reinterpret_vec(vec![0_u16]),
+            LanceBuffer::reinterpret_vec(vec![0_u16]),
+            LanceBuffer::reinterpret_vec(vec![0_u16]),
+        ];
+        assert!(LanceBuffer::morton_encode_nway(&wide, 13).is_err());
+        assert!(LanceBuffer::morton_encode_nway(&wide[..4], 16).is_ok());
+        assert!(LanceBuffer::morton_encode_nway(&[], 8).is_err());
+        assert!(
+            LanceBuffer::morton_encode_nway(&[dims[0].deep_copy(), wide[0].deep_copy()], 16)
+                .is_err()
+        );
+        assert!(LanceBuffer::Owned(vec![0; 7])
+            .morton_decode_nway(3, 13)
+            .is_err());
+    }
+
+    #[cfg(feature = "checksum")]
+    #[test]
+    fn test_crc32c() {
+        // Known answers from RFC 3720 (iSCSI) appendix B.4
+        assert_eq!(LanceBuffer::empty().crc32c(), 0);
         assert_eq!(
-            expected,
-            LanceBuffer::concat_into_one(vec![buf1, buf2, buf3])
+            LanceBuffer::Owned(b"123456789".to_vec()).crc32c(),
+            0xE306_9283
+        );
+        assert_eq!(LanceBuffer::Owned(vec![0; 32]).crc32c(), 0x8A91_36AA);
+        assert_eq!(LanceBuffer::Owned(vec![0xFF; 32]).crc32c(), 0x62A8_AB43);
+        assert_eq!(
+            LanceBuffer::Owned((0..32).collect()).crc32c(),
+            LanceBuffer::Borrowed(Buffer::from_vec((0..32).collect::<Vec<u8>>())).crc32c()
         );
+        assert_eq!(LanceBuffer::Owned((0..32).collect()).crc32c(), 0x46DD_794E);
+    }
 
-        let empty = LanceBuffer::empty();
+    #[test]
+    fn test_checked_add_assign_values() {
+        let mut counters = LanceBuffer::reinterpret_vec(vec![1_u16, 2, 3, 65000]);
+        let increments = LanceBuffer::reinterpret_vec(vec![10_u16, 20, 30, 535]);
+        counters
+            .checked_add_assign_values::<u16>(&increments)
+            .unwrap();
+        assert_eq!(
+            counters,
+            LanceBuffer::reinterpret_vec(vec![11_u16, 22, 33, 65535])
+        );
+
+        let ones = LanceBuffer::reinterpret_vec(vec![1_u16; 4]);
+        let err = counters
+            .checked_add_assign_values::<u16>(&ones)
+            .unwrap_err();
+        assert!(matches!(err, lance_core::Error::InvalidInput { .. }));
+        assert!(err.to_string().contains("index 3"), "{}", err);
+        // The values are untouched when an overflow is reported
+        assert_eq!(
+            counters,
+            LanceBuffer::reinterpret_vec(vec![11_u16, 22, 33, 65535])
+        );
+
+        let mut signed = LanceBuffer::reinterpret_vec(vec![-5_i32, i32::MIN + 1]);
+        signed
+            .checked_add_assign_values::<i32>(&LanceBuffer::reinterpret_vec(vec![5_i32, -1]))
+            .unwrap();
+        assert_eq!(signed, LanceBuffer::reinterpret_vec(vec![0_i32, i32::MIN]));
+        assert!(signed
+            .checked_add_assign_values::<i32>(&LanceBuffer::reinterpret_vec(vec![0_i32, -1]))
+            .is_err());
+
+        assert!(counters
+            .checked_add_assign_values::<u16>(&LanceBuffer::Owned(vec![0; 6]))
+            .is_err());
+        assert!(LanceBuffer::Owned(vec![0; 3])
+            .checked_add_assign_values::<u16>(&LanceBuffer::Owned(vec![0; 3]))
+            .is_err());
+    }
+
+    #[cfg(feature = "hash")]
+    #[test]
+    fn test_content_hash() {
+        let data = (0..100).collect::<Vec<u8>>();
+        let owned = LanceBuffer::Owned(data.clone());
+        let borrowed = LanceBuffer::Borrowed(Buffer::from_vec(data.clone()));
+        let mut shared = LanceBuffer::Owned(data.clone());
+        let shared = shared.share();
+        assert_eq!(owned.content_hash(), borrowed.content_hash());
+        assert_eq!(owned.content_hash(), shared.content_hash());
+        assert_eq!(
+            borrowed.slice_with_length(10, 20).content_hash(),
+            LanceBuffer::Owned(data[10..30].to_vec()).content_hash()
+        );
+
+        assert_ne!(
+            owned.content_hash(),
+            LanceBuffer::Owned(data[1..].to_vec()).content_hash()
+        );
+        assert_ne!(
+            LanceBuffer::empty().content_hash(),
+            LanceBuffer::Owned(vec![0]).content_hash()
+        );
+    }
+
+    #[cfg(feature = "checksum")]
+    #[test]
+    fn test_pack_block() {
+        use super::Codec;
+
+        let data = (0..10_000_u32)
+            .flat_map(|i| (i % 100).to_le_bytes())
+            .collect::<Vec<_>>();
+        let buffer = LanceBuffer::Owned(data.clone());
+        let codecs = [
+            Codec::None,
+            #[cfg(feature = "zstd")]
+            Codec::Zstd,
+            #[cfg(feature = "lz4")]
+            Codec::Lz4,
+        ];
+        for codec in codecs {
+            let block = buffer.pack_block(codec).unwrap();
+            assert_eq!(&block[0..4], b"LNCB");
+            assert_eq!(block[4], codec.id());
+            assert_eq!(Codec::from_id(codec.id()).unwrap(), codec);
+            assert_eq!(u64::from_le_bytes(block[5..13].try_into().unwrap()), 40_000);
+            if codec != Codec::None {
+                assert!(block.len() < data.len());
+            }
+            assert_eq!(block.unpack_block().unwrap(), buffer);
+            assert!(LanceBuffer::empty()
+                .pack_block(codec)
+                .unwrap()
+                .unpack_block()
+                .unwrap()
+                .is_empty());
+        }
+
+        let block = buffer.pack_block(Codec::None).unwrap().to_vec();
+        let unpack_err = |block: Vec<u8>| {
+            LanceBuffer::Owned(block)
+                .unpack_block()
+                .unwrap_err()
+                .to_string()
+        };
+
+        let mut bad_magic = block.clone();
+        bad_magic[0] = b'X';
+        assert!(unpack_err(bad_magic).contains("not a packed block"));
+
+        let mut bad_codec = block.clone();
+        bad_codec[4] = 9;
+        assert!(unpack_err(bad_codec).contains("unknown codec id 9"));
+
+        let mut bad_crc = block.clone();
+        bad_crc[13] ^= 1;
+        assert!(unpack_err(bad_crc).contains("checksum mismatch"));
+
+        let mut truncated = block.clone();
+        truncated.pop();
+        assert!(unpack_err(truncated).contains("payload is 39999 bytes"));
+
+        #[cfg(feature = "zstd")]
+        {
+            let mut truncated = buffer.pack_block(Codec::Zstd).unwrap().to_vec();
+            truncated.truncate(truncated.len() - 10);
+            assert!(unpack_err(truncated).contains("could not be decompressed"));
+        }
+        #[cfg(not(feature = "lz4"))]
+        {
+            let mut lz4_block = block.clone();
+            lz4_block[4] = 2;
+            assert!(unpack_err(lz4_block).contains("requires the lz4 feature"));
+        }
+
+        // A corrupt length is rejected before anything is allocated
+        for codec in codecs {
+            for bad_len in [u64::MAX, 1 << 40, i32::MAX as u64, 40_001] {
+                let mut bad_len_block = buffer.pack_block(codec).unwrap().to_vec();
+                bad_len_block[5..13].copy_from_slice(&bad_len.to_le_bytes());
+                let err = unpack_err(bad_len_block);
+                assert!(!err.contains("checksum mismatch"), "{}", err);
+            }
+        }
+
+        assert!(unpack_err(block[..10].to_vec()).contains("truncated block"));
+    }
+
+    #[test]
+    fn test_hash() {
+        use std::collections::HashSet;
+
+        let data = (0..64).collect::<Vec<u8>>();
+        let mut set = HashSet::new();
+        set.insert(LanceBuffer::Owned(data.clone()));
+        set.insert(LanceBuffer::Borrowed(Buffer::from_vec(data.clone())));
+        set.insert(LanceBuffer::Owned(data.clone()).share());
+        assert_eq!(set.len(), 1);
+        assert!(set.contains(&LanceBuffer::copy_slice(&data)));
+
+        set.insert(LanceBuffer::Owned(data[1..].to_vec()));
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn test_ord() {
+        use std::cmp::Ordering;
+        use std::collections::BTreeMap;
+
+        let short = LanceBuffer::Owned(vec![1, 2]);
+        let long = LanceBuffer::Borrowed(Buffer::from_vec(vec![1_u8, 2, 3]));
+        assert!(short < long);
+        assert!(LanceBuffer::empty() < short);
+        assert!(LanceBuffer::Owned(vec![1, 3]) > long);
         assert_eq!(
+            short.cmp(&LanceBuffer::Borrowed(Buffer::from_vec(vec![1_u8, 2]))),
+            Ordering::Equal
+        );
+
+        let mut buffers = vec![
+            LanceBuffer::Owned(vec![2]),
+            LanceBuffer::Owned(vec![1, 2, 3]),
             LanceBuffer::empty(),
-            LanceBuffer::concat_into_one(vec![empty])
+            LanceBuffer::Borrowed(Buffer::from_vec(vec![1_u8, 2])),
+        ];
+        buffers.sort();
+        assert_eq!(
+            buffers,
+            vec![
+                LanceBuffer::empty(),
+                LanceBuffer::Owned(vec![1, 2]),
+                LanceBuffer::Owned(vec![1, 2, 3]),
+                LanceBuffer::Owned(vec![2]),
+            ]
         );
 
-        let expected = LanceBuffer::Owned(vec![1, 2, 3]);
+        let mut map = BTreeMap::new();
+        map.insert(LanceBuffer::Owned(vec![5]), "owned");
+        map.insert(
+            LanceBuffer::Borrowed(Buffer::from_vec(vec![5_u8])),
+            "borrowed",
+        );
+        map.insert(LanceBuffer::Owned(vec![4, 9]), "other");
+        assert_eq!(map.len(), 2);
+        assert_eq!(map[&LanceBuffer::Owned(vec![5])], "borrowed");
+        assert_eq!(map.keys().next().unwrap().as_ref(), &[4, 9]);
+    }
+
+    #[cfg(feature = "base64")]
+    #[test]
+    fn test_base64() {
+        let buffer = LanceBuffer::Borrowed(Buffer::from_vec(b"lance!".to_vec()));
+        assert_eq!(buffer.as_base64(), "bGFuY2Uh");
+        assert_eq!(LanceBuffer::Owned(vec![0xFF, 0xEE]).as_base64(), "/+4=");
+
+        let decoded = LanceBuffer::from_base64("bGFuY2Uh").unwrap();
+        assert!(matches!(decoded, LanceBuffer::Owned(_)));
+        assert_eq!(decoded, buffer);
+        let random = LanceBuffer::Owned((0..=255).collect());
         assert_eq!(
-            expected,
-            LanceBuffer::concat_into_one(vec![expected.deep_copy(), LanceBuffer::empty()])
+            LanceBuffer::from_base64(&random.as_base64()).unwrap(),
+            random
         );
+        assert!(LanceBuffer::from_base64("").unwrap().is_empty());
+
+        for invalid in ["not base64!", "bGFuY2U", "/+4"] {
+            let err = LanceBuffer::from_base64(invalid).unwrap_err();
+            assert!(matches!(err, lance_core::Error::InvalidInput { .. }));
+        }
     }
 
+    #[cfg(feature = "zstd")]
     #[test]
-    fn test_zip() {
-        let buf1 = LanceBuffer::Owned(vec![1_u8, 2, 3]);
-        let buf2 = LanceBuffer::reinterpret_vec(vec![1_u16, 2, 3]);
-        let buf3 = LanceBuffer::reinterpret_vec(vec![1_u32, 2, 3]);
+    fn test_compress_zstd() {
+        let compressible = LanceBuffer::Owned(
+            (0..4096_u32)
+                .flat_map(|i| (i % 16).to_le_bytes())
+                .collect::<Vec<_>>(),
+        );
+        let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+        let mut random = vec![0_u8; 8192];
+        rng.fill_bytes(&mut random);
+        let random = LanceBuffer::Owned(random);
 
-        let zipped = LanceBuffer::zip_into_one(vec![(buf1, 8), (buf2, 16), (buf3, 32)], 3).unwrap();
+        for buffer in [&compressible, &random, &LanceBuffer::empty()] {
+            let compressed = buffer.compress_zstd(3).unwrap();
+            assert!(matches!(compressed, LanceBuffer::Owned(_)));
+            let decompressed = compressed.decompress_zstd(buffer.len()).unwrap();
+            assert!(matches!(decompressed, LanceBuffer::Owned(_)));
+            assert_eq!(&decompressed, buffer);
+        }
+        let compressed = compressible.compress_zstd(3).unwrap();
+        assert!(compressed.len() < compressible.len() / 10);
 
-        assert_eq!(zipped.len(), 21);
+        // Too small or too large an expected length
+        assert!(compressed.decompress_zstd(compressible.len() - 1).is_err());
+        let err = compressed
+            .decompress_zstd(compressible.len() + 1)
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("instead of the expected"),
+            "{}",
+            err
+        );
 
-        let mut expected = Vec::with_capacity(21);
-        for i in 1..4 {
-            expected.push(i as u8);
-            expected.extend_from_slice(&(i as u16).to_ne_bytes());
-            expected.extend_from_slice(&(i as u32).to_ne_bytes());
+        let truncated = compressed.slice_with_length(0, compressed.len() / 2);
+        assert!(truncated.decompress_zstd(compressible.len()).is_err());
+
+        // A huge expected length is rejected rather than allocated
+        assert!(compressed.decompress_zstd(usize::MAX).is_err());
+
+        // Frames that do not record their content size are decompressed incrementally
+        let unsized_frame = zstd::stream::encode_all(compressible.as_ref(), 3).unwrap();
+        assert_eq!(
+            zstd::zstd_safe::get_frame_content_size(&unsized_frame).unwrap(),
+            None
+        );
+        let unsized_frame = LanceBuffer::Owned(unsized_frame);
+        assert_eq!(
+            unsized_frame.decompress_zstd(compressible.len()).unwrap(),
+            compressible
+        );
+        assert!(unsized_frame
+            .decompress_zstd(compressible.len() - 1)
+            .is_err());
+        assert!(unsized_frame.decompress_zstd(usize::MAX).is_err());
+    }
+
+    #[cfg(feature = "lz4")]
+    #[test]
+    fn test_compress_lz4() {
+        let compressible = LanceBuffer::Owned(
+            (0..4096_u32)
+                .flat_map(|i| (i % 16).to_le_bytes())
+                .collect::<Vec<_>>(),
+        );
+        let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+        let mut random = vec![0_u8; 8192];
+        rng.fill_bytes(&mut random);
+        let random = LanceBuffer::Owned(random);
+
+        for buffer in [&compressible, &random, &LanceBuffer::empty()] {
+            let compressed = buffer.compress_lz4().unwrap();
+            let decompressed = compressed.decompress_lz4(buffer.len()).unwrap();
+            assert!(matches!(decompressed, LanceBuffer::Owned(_)));
+            assert_eq!(&decompressed, buffer);
         }
-        let expected = LanceBuffer::Owned(expected);
+        let compressed = compressible.compress_lz4().unwrap();
+        assert!(compressed.len() < compressible.len() / 10);
 
-        assert_eq!(expected, zipped);
+        assert!(compressed.decompress_lz4(compressible.len() - 1).is_err());
+        let err = compressed
+            .decompress_lz4(compressible.len() + 1)
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("instead of the expected"),
+            "{}",
+            err
+        );
+
+        let truncated = compressed.slice_with_length(0, compressed.len() / 2);
+        assert!(truncated.decompress_lz4(compressible.len()).is_err());
+        // Lengths that no LZ4 block of this size could produce are rejected before allocating
+        let err = compressed.decompress_lz4(usize::MAX).unwrap_err();
+        assert!(
+            err.to_string().contains("from an lz4 block of only"),
+            "{}",
+            err
+        );
+        assert!(compressed
+            .decompress_lz4(compressed.len() * 255 + 1)
+            .is_err());
+        let mut garbage = vec![0xFF_u8; 64];
+        garbage[0] = 0xF0;
+        assert!(LanceBuffer::Owned(garbage).decompress_lz4(4096).is_err());
     }
 
     #[test]
-    fn test_hex() {
-        let buf = LanceBuffer::Owned(vec![1, 2, 15, 20]);
-        assert_eq!("01020F14", buf.as_hex());
+    fn test_swap_bytes() {
+        let big_endian = LanceBuffer::Owned(vec![0x12, 0x34, 0xAB, 0xCD]);
+        let mut buffer = big_endian.deep_copy();
+        buffer.swap_bytes_in_place::<u16>().unwrap();
+        assert_eq!(
+            buffer,
+            LanceBuffer::reinterpret_vec(vec![0x1234_u16, 0xABCD])
+        );
+        assert_eq!(
+            big_endian.to_endian_swapped::<u32>().unwrap(),
+            LanceBuffer::reinterpret_vec(vec![0x1234_ABCD_u32])
+        );
+
+        let values = vec![1.5_f64, -2.25, f64::MAX];
+        let big_endian = LanceBuffer::Owned(values.iter().flat_map(|v| v.to_be_bytes()).collect());
+        let swapped = big_endian.to_endian_swapped::<f64>().unwrap();
+        assert_eq!(swapped, LanceBuffer::reinterpret_vec(values.clone()));
+        let mut round_trip = LanceBuffer::Borrowed(Buffer::from_vec(values.clone()));
+        round_trip.swap_bytes_in_place::<f64>().unwrap();
+        assert_eq!(round_trip, big_endian);
+        round_trip.swap_bytes_in_place::<f64>().unwrap();
+        assert_eq!(round_trip, LanceBuffer::reinterpret_vec(values));
+
+        let mut odd = LanceBuffer::Owned(vec![0; 6]);
+        assert!(odd.swap_bytes_in_place::<u32>().is_err());
+        assert!(odd.to_endian_swapped::<u64>().is_err());
+        assert_eq!(odd, LanceBuffer::Owned(vec![0; 6]));
     }
 
+    #[cfg(feature = "mmap")]
     #[test]
-    #[should_panic]
-    fn test_to_typed_slice_invalid() {
-        let mut buf = LanceBuffer::Owned(vec![0, 1, 2]);
-        buf.borrow_to_typed_slice::<u16>();
+    fn test_mmap() {
+        use std::io::Write;
+
+        let data = (0..20_000_u32).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(&data).unwrap();
+        file.flush().unwrap();
+
+        let buffer = LanceBuffer::mmap(file.path(), 4096, 10_000).unwrap();
+        assert!(matches!(buffer, LanceBuffer::Mapped(_)));
+        assert_eq!(buffer.as_ref(), &data[4096..14_096]);
+
+        // Slices keep the mapping alive after the original buffer is dropped
+        let slice = buffer.slice_with_length(100, 10);
+        drop(buffer);
+        assert!(matches!(slice, LanceBuffer::Mapped(_)));
+        assert_eq!(slice.as_ref(), &data[4196..4206]);
+        let arrow = slice.try_clone().unwrap().into_buffer();
+        assert_eq!(arrow.as_slice(), &data[4196..4206]);
+
+        // The offset does not need to be page aligned
+        let unaligned = LanceBuffer::mmap(file.path(), 3, 5).unwrap();
+        assert_eq!(unaligned.as_ref(), &data[3..8]);
+
+        assert!(LanceBuffer::mmap(file.path(), 20_000, 0)
+            .unwrap()
+            .is_empty());
+        let err = LanceBuffer::mmap(file.path(), 19_999, 2).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        assert!(LanceBuffer::mmap(&file.path().with_extension("missing"), 0, 1).is_err());
     }
 
+    #[cfg(feature = "mmap")]
     #[test]
-    fn test_to_typed_slice() {
-        // Buffer is aligned, no copy will be made, both calls
-        // should get same ptr
-        let mut buf = LanceBuffer::Owned(vec![0, 1]);
-        let borrow = buf.borrow_to_typed_slice::<u16>();
-        let view_ptr = borrow.as_ref().as_ptr();
-        let borrow2 = buf.borrow_to_typed_slice::<u16>();
-        let view_ptr2 = borrow2.as_ref().as_ptr();
+    fn test_advise() {
+        use std::io::Write;
 
-        assert_eq!(view_ptr, view_ptr2);
+        use super::Advice;
 
-        let bytes = bytes::Bytes::from(vec![0, 1, 2]);
-        let sliced = bytes.slice(1..3);
-        // Intentionally LYING about alignment here to trigger test
-        let mut buf = LanceBuffer::from_bytes(sliced, 1);
-        let borrow = buf.borrow_to_typed_slice::<u16>();
-        let view_ptr = borrow.as_ref().as_ptr();
-        let borrow2 = buf.borrow_to_typed_slice::<u16>();
-        let view_ptr2 = borrow2.as_ref().as_ptr();
+        let data = (0..100_000_u32)
+            .map(|i| (i % 251) as u8)
+            .collect::<Vec<_>>();
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(&data).unwrap();
+        file.flush().unwrap();
 
-        assert_ne!(view_ptr, view_ptr2);
+        let buffer = LanceBuffer::mmap(file.path(), 10, 90_000).unwrap();
+        let slice = buffer.slice_with_length(5000, 20_000);
+        for advice in [
+            Advice::Sequential,
+            Advice::Random,
+            Advice::WillNeed,
+            Advice::DontNeed,
+        ] {
+            buffer.advise(advice).unwrap();
+            slice.advise(advice).unwrap();
+        }
+        // Dropped pages are read back from the file
+        assert_eq!(buffer.as_ref(), &data[10..90_010]);
+        assert_eq!(slice.as_ref(), &data[5010..25_010]);
+
+        // Buffers that are not memory-mapped are left alone
+        let owned = LanceBuffer::Owned(data.clone());
+        owned.advise(Advice::DontNeed).unwrap();
+        assert_eq!(owned.as_ref(), data.as_slice());
+        let borrowed = LanceBuffer::Borrowed(Buffer::from_vec(data.clone()));
+        borrowed.advise(Advice::DontNeed).unwrap();
+        assert_eq!(borrowed.as_ref(), data.as_slice());
+        LanceBuffer::empty().advise(Advice::WillNeed).unwrap();
     }
 
     #[test]
-    fn test_bit_slice_le() {
-        let mut buf = LanceBuffer::Owned(vec![0x0F, 0x0B]);
+    fn test_extend_and_append() {
+        let mut buffer = LanceBuffer::empty();
+        buffer.reserve(1024);
+        let LanceBuffer::Owned(data) = &buffer else {
+            panic!("expected an owned buffer");
+        };
+        let ptr = data.as_ptr();
+        assert!(data.capacity() >= 1024);
+        for i in 0..128_u8 {
+            buffer.extend_from_slice(&[i; 8]);
+        }
+        // No reallocation happened
+        assert_eq!(buffer.as_ptr(), ptr);
+        assert_eq!(buffer.len(), 1024);
+        assert_eq!(&buffer[1016..], &[127; 8]);
 
-        // Keep in mind that validity buffers are *bitwise* little-endian
-        assert_eq!(buf.bit_slice_le_with_length(0, 4).as_ref(), &[0x0F]);
-        assert_eq!(buf.bit_slice_le_with_length(4, 4).as_ref(), &[0x00]);
-        assert_eq!(buf.bit_slice_le_with_length(3, 8).as_ref(), &[0x61]);
-        assert_eq!(buf.bit_slice_le_with_length(0, 8).as_ref(), &[0x0F]);
-        assert_eq!(buf.bit_slice_le_with_length(4, 8).as_ref(), &[0xB0]);
-        assert_eq!(buf.bit_slice_le_with_length(4, 12).as_ref(), &[0xB0, 0x00]);
+        // Borrowed buffers are promoted to owned on the first mutation
+        let mut buffer = LanceBuffer::Borrowed(Buffer::from_vec(vec![1_u8, 2]));
+        buffer.extend_from_slice(&[3]);
+        assert!(matches!(buffer, LanceBuffer::Owned(_)));
+        buffer.append(LanceBuffer::Borrowed(Buffer::from_vec(vec![4_u8, 5])));
+        buffer.append(LanceBuffer::Owned(vec![6]).share());
+        buffer.append(LanceBuffer::empty());
+        assert_eq!(buffer.as_ref(), &[1, 2, 3, 4, 5, 6]);
+
+        let original = LanceBuffer::Borrowed(Buffer::from_vec(vec![7_u8, 8]));
+        let mut clone = original.try_clone().unwrap();
+        clone.append(LanceBuffer::Owned(vec![9]));
+        assert_eq!(clone.as_ref(), &[7, 8, 9]);
+        assert_eq!(original.as_ref(), &[7, 8]);
+    }
+
+    #[test]
+    fn test_make_mut() {
+        let original = LanceBuffer::Borrowed(Buffer::from_vec(vec![1_u8, 2, 3]));
+        let mut clone = original.try_clone().unwrap();
+        clone.make_mut()[0] = 100;
+        assert!(matches!(clone, LanceBuffer::Owned(_)));
+        assert_eq!(clone.as_ref(), &[100, 2, 3]);
+        assert_eq!(original.as_ref(), &[1, 2, 3]);
+
+        let mut shared = LanceBuffer::Owned(vec![4_u8, 5]);
+        let other = shared.share();
+        shared.make_mut()[1] = 50;
+        assert_eq!(shared.as_ref(), &[4, 50]);
+        assert_eq!(other.as_ref(), &[4, 5]);
+
+        // Owned buffers, and borrowed buffers that are the only reference, are not copied
+        let mut owned = LanceBuffer::Owned(vec![6_u8, 7]);
+        let ptr = owned.as_ptr();
+        owned.make_mut()[0] = 60;
+        assert_eq!(owned.as_ptr(), ptr);
+        let mut sole = LanceBuffer::Borrowed(Buffer::from_vec(vec![8_u8, 9]));
+        let ptr = sole.as_ptr();
+        sole.make_mut()[1] = 90;
+        assert_eq!(sole.as_ptr(), ptr);
+        assert_eq!(sole.as_ref(), &[8, 90]);
+    }
+
+    #[test]
+    fn test_split_at() {
+        let data = (0..10).collect::<Vec<u8>>();
+        let buffer = LanceBuffer::Borrowed(Buffer::from_vec(data.clone()));
+        let base = buffer.as_ptr();
+        let (first, second) = buffer.split_at(4);
+        assert_eq!(first.as_ref(), &data[..4]);
+        assert_eq!(second.as_ref(), &data[4..]);
+        // Both halves are slices of the same allocation
+        let (LanceBuffer::Borrowed(first), LanceBuffer::Borrowed(second)) = (&first, &second)
+        else {
+            panic!("expected borrowed halves");
+        };
+        assert_eq!(first.data_ptr(), second.data_ptr());
+        assert_eq!(first.as_ptr(), base);
+        assert_eq!(second.as_ptr(), base.wrapping_add(4));
+
+        let owned = LanceBuffer::Owned(data.clone());
+        let base = owned.as_ptr();
+        let (first, second) = owned.split_at(0);
+        assert!(first.is_empty());
+        assert_eq!(second.as_ref(), data.as_slice());
+        assert_eq!(second.as_ptr(), base);
+
+        let (first, second) = LanceBuffer::Owned(data.clone()).share().split_at(10);
+        assert_eq!(first.as_ref(), data.as_slice());
+        assert!(second.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot exceed the buffer length")]
+    fn test_split_at_out_of_bounds() {
+        LanceBuffer::Owned(vec![1, 2, 3]).split_at(4);
+    }
+
+    #[test]
+    fn test_chunks() {
+        let buffer = LanceBuffer::Borrowed(Buffer::from_vec((0..10).collect::<Vec<u8>>()));
+        let chunks = buffer.chunks(4).collect::<Vec<_>>();
+        assert_eq!(chunks, vec![&[0, 1, 2, 3][..], &[4, 5, 6, 7], &[8, 9]]);
+        assert_eq!(buffer.chunks(5).count(), 2);
+        assert_eq!(
+            buffer.chunks(100).collect::<Vec<_>>(),
+            vec![buffer.as_ref()]
+        );
+        assert_eq!(LanceBuffer::empty().chunks(4).count(), 0);
+
+        let mut exact = buffer.chunks_exact(4);
+        assert_eq!(exact.next(), Some(&[0, 1, 2, 3][..]));
+        assert_eq!(exact.next(), Some(&[4, 5, 6, 7][..]));
+        assert_eq!(exact.next(), None);
+        assert_eq!(exact.remainder(), &[8, 9]);
+        assert!(buffer.chunks_exact(5).remainder().is_empty());
+        let mut too_big = buffer.chunks_exact(11);
+        assert_eq!(too_big.next(), None);
+        assert_eq!(too_big.remainder(), buffer.as_ref());
+    }
+
+    #[test]
+    fn test_slice_shared() {
+        let data = (0..100).collect::<Vec<u8>>();
+        let mut buffer = LanceBuffer::Owned(data.clone());
+        let base = buffer.as_ptr();
+
+        let first = buffer.slice_shared(10, 20);
+        let second = buffer.slice_shared(50, 5);
+        assert!(matches!(buffer, LanceBuffer::Borrowed(_)));
+        assert_eq!(first.as_ref(), &data[10..30]);
+        assert_eq!(second.as_ref(), &data[50..55]);
+        let (LanceBuffer::Borrowed(first), LanceBuffer::Borrowed(second)) = (&first, &second)
+        else {
+            panic!("expected borrowed slices");
+        };
+        assert_eq!(first.data_ptr(), second.data_ptr());
+        assert_eq!(first.as_ptr(), base.wrapping_add(10));
+        assert_eq!(second.as_ptr(), base.wrapping_add(50));
+
+        // slice_with_length still copies owned buffers
+        let owned = LanceBuffer::Owned(data.clone());
+        assert_ne!(
+            owned.slice_with_length(10, 20).as_ptr(),
+            owned.as_ptr().wrapping_add(10)
+        );
+
+        let mut shared = LanceBuffer::Owned(data.clone()).share();
+        assert_eq!(shared.slice_shared(90, 10).as_ref(), &data[90..]);
+    }
+
+    #[test]
+    fn test_take_fixed_width() {
+        let buffer = LanceBuffer::reinterpret_vec(vec![10_u32, 20, 30, 40]);
+        let taken = buffer.take_fixed_width(&[3, 0, 2, 1], 4).unwrap();
+        assert!(matches!(taken, LanceBuffer::Owned(_)));
+        assert_eq!(
+            taken,
+            LanceBuffer::reinterpret_vec(vec![40_u32, 10, 30, 20])
+        );
+
+        let taken = buffer.take_fixed_width(&[1, 1, 3, 1], 4).unwrap();
+        assert_eq!(
+            taken,
+            LanceBuffer::reinterpret_vec(vec![20_u32, 20, 40, 20])
+        );
+        assert!(buffer.take_fixed_width(&[], 4).unwrap().is_empty());
+
+        // Odd widths
+        let records = LanceBuffer::Owned(b"aaabbbccc".to_vec());
+        assert_eq!(
+            records.take_fixed_width(&[2, 0], 3).unwrap().as_ref(),
+            b"cccaaa"
+        );
+
+        let err = buffer.take_fixed_width(&[0, 4], 4).unwrap_err();
+        assert!(
+            err.to_string().contains("index 4 (at position 1)"),
+            "{}",
+            err
+        );
+        assert!(buffer.take_fixed_width(&[0], 0).is_err());
+        assert!(buffer.take_fixed_width(&[0], 3).is_err());
+    }
+
+    #[test]
+    fn test_filter_fixed_width() {
+        let buffer = LanceBuffer::reinterpret_vec((0..10_u16).collect::<Vec<_>>());
+
+        let all = LanceBuffer::Owned(vec![0xFF, 0x03]);
+        assert_eq!(buffer.filter_fixed_width(&all, 2).unwrap(), buffer);
+
+        let none = LanceBuffer::Owned(vec![0, 0]);
+        assert!(buffer.filter_fixed_width(&none, 2).unwrap().is_empty());
+
+        // Bits past the last value are ignored
+        let alternating = LanceBuffer::Owned(vec![0b0101_0101, 0b1111_1101]);
+        let filtered = buffer.filter_fixed_width(&alternating, 2).unwrap();
+        assert!(matches!(filtered, LanceBuffer::Owned(_)));
+        assert_eq!(
+            filtered,
+            LanceBuffer::reinterpret_vec(vec![0_u16, 2, 4, 6, 8])
+        );
+
+        let records = LanceBuffer::Owned(b"aaabbbccc".to_vec());
+        assert_eq!(
+            records
+                .filter_fixed_width(&LanceBuffer::Owned(vec![0b110]), 3)
+                .unwrap()
+                .as_ref(),
+            b"bbbccc"
+        );
+
+        assert!(buffer
+            .filter_fixed_width(&LanceBuffer::Owned(vec![0xFF]), 2)
+            .is_err());
+        assert!(buffer.filter_fixed_width(&all, 0).is_err());
+        assert!(buffer.filter_fixed_width(&all, 3).is_err());
+    }
+
+    #[test]
+    fn test_from_bools() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+        for len in [0_usize, 7, 8, 9, 64] {
+            let bits = (0..len).map(|_| rng.gen_bool(0.5)).collect::<Vec<_>>();
+            let bitmap = LanceBuffer::from_bools(&bits);
+            assert_eq!(bitmap.len(), len.div_ceil(8));
+            assert_eq!(bitmap.to_bools(len), bits);
+            assert_eq!(
+                bitmap.count_ones() as usize,
+                bits.iter().filter(|b| **b).count()
+            );
+        }
+
+        let bitmap = LanceBuffer::from_bools(&[
+            true, false, false, true, true, true, true, true, false, true,
+        ]);
+        assert_eq!(bitmap.as_ref(), &[0b1111_1001, 0b10]);
+
+        // Padding bits are ignored
+        let padded = LanceBuffer::Owned(vec![0xFF, 0xFF]);
+        assert_eq!(padded.to_bools(9), vec![true; 9]);
+        assert!(padded.to_bools(0).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "to_bools called with 17 bits")]
+    fn test_to_bools_out_of_bounds() {
+        LanceBuffer::Owned(vec![0, 0]).to_bools(17);
+    }
+
+    #[test]
+    fn test_get_set_bit() {
+        let original = LanceBuffer::Borrowed(Buffer::from_vec(vec![0b1010_0101_u8, 0]));
+        let mut bitmap = original.try_clone().unwrap();
+        assert!(bitmap.get_bit(0));
+        assert!(!bitmap.get_bit(1));
+        assert!(bitmap.get_bit(7));
+        assert!(!bitmap.get_bit(15));
+
+        bitmap.set_bit(1, true);
+        assert!(matches!(bitmap, LanceBuffer::Owned(_)));
+        assert!(bitmap.get_bit(1));
+        bitmap.set_bit(0, false);
+        assert!(!bitmap.get_bit(0));
+        bitmap.set_bit(15, true);
+        bitmap.set_bit(15, true);
+        assert!(bitmap.get_bit(15));
+        // Neighboring bits are untouched
+        assert_eq!(bitmap.as_ref(), &[0b1010_0110, 0b1000_0000]);
+        assert_eq!(original.as_ref(), &[0b1010_0101, 0]);
+
+        // Consistent with bit_slice_le_with_length
+        let mut sliced_source = bitmap.deep_copy();
+        let sliced = sliced_source.bit_slice_le_with_length(1, 8);
+        assert_eq!(
+            (0..8).map(|i| sliced.get_bit(i)).collect::<Vec<_>>(),
+            (1..9).map(|i| bitmap.get_bit(i)).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "set_bit called with bit 16")]
+    fn test_set_bit_out_of_bounds() {
+        LanceBuffer::Owned(vec![0, 0]).set_bit(16, true);
     }
 }
@@ -222,6 +222,16 @@ impl ArrayEncoder for PackedStructEncoder {
                 )),
             })
             .collect::<Result<Vec<_>>>()?;
+        // The decoder slices each row at byte boundaries so sub-byte fields can't be packed
+        if let Some(field) = fixed_fields.iter().find(|f| f.bits_per_value % 8 != 0) {
+            return Err(Error::invalid_input(
+                format!(
+                    "Packed struct encoder only supports full-byte fields and received a field with {} bits per value",
+                    field.bits_per_value
+                ),
+                location!(),
+            ));
+        }
         let total_bits_per_value = fixed_fields.iter().map(|f| f.bits_per_value).sum::<u64>();
 
         let num_values = fixed_fields[0].num_values;